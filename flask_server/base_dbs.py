@@ -47,6 +47,12 @@ def create_type_db():
     cur.execute("INSERT INTO blobs VALUES (?)", (import_basefile("spriteConfig.json"),))
     cur.execute("INSERT INTO blobs VALUES (?)", (import_basefile("audioConfig.json"),))
     cur.execute("INSERT INTO blobs VALUES (?)", (import_basefile("fontConfig.json"),))
+    cur.execute("INSERT INTO blobs VALUES (?)", (import_basefile("behaviorScript.rhai"),))
+    cur.execute("INSERT INTO blobs VALUES (?)", (import_basefile("behaviorConfig.json"),))
+    cur.execute("INSERT INTO blobs VALUES (?)", (import_basefile("libraryScript.rhai"),))
+    cur.execute("INSERT INTO blobs VALUES (?)", (import_basefile("libraryConfig.json"),))
+    cur.execute("INSERT INTO blobs VALUES (?)", (import_basefile("testScript.rhai"),))
+    cur.execute("INSERT INTO blobs VALUES (?)", (import_basefile("testConfig.json"),))
 
     db.commit()
     cur.close()
@@ -18,7 +18,7 @@
 //          Imports         //
 
 import materialSetup, {switchMaterial, 
-    resetMaterial} from "./DOMcontrol/material.js";
+    resetMaterial, getScriptCursorLine} from "./DOMcontrol/material.js";
 
 import actionMenusSetup, {openContextMenu,
     closeActionMenus} from "./DOMcontrol/action-menus.js";
@@ -209,5 +209,5 @@ function documentInteractionSetup(dropDownFn = {}, contextFn = {}, switchFn = {}
 // We export the things we want the
 // importing script to be able to use.
 
-export { addItem, removeItem, fromItemToTab, openMessageWindow, openInputWindow, switchMaterial, resetMaterial };
+export { addItem, removeItem, fromItemToTab, openMessageWindow, openInputWindow, switchMaterial, resetMaterial, getScriptCursorLine };
 export default documentInteractionSetup;
\ No newline at end of file
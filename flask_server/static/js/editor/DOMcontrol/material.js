@@ -606,8 +606,15 @@ wasmLoadPromise.then(() => {
     rhaiScript.setOption("mode", "rhai");
 });
 
+// This function returns the line number
+// (starting from 1) of the script editor's
+// cursor, which the debugger's breakpoints use.
+function getScriptCursorLine() {
+    return rhaiScript.getCursor().line + 1;
+}
+
 // We export the things we want the
 // importing script to be able to use.
 
-export { switchMaterial, resetMaterial };
+export { switchMaterial, resetMaterial, getScriptCursorLine };
 export default materialSetup;
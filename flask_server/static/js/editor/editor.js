@@ -135,6 +135,23 @@ self.setupTestWindow = function(new_window) {
         elementsToLoad = [];
         return toLoad;
     };
+    // Gives a list of the rowids and types
+    // of all the elements in the game, which
+    // the engine core's test runner loads
+    // into a fresh copy of the game.
+    new_window.self.allElements = function() {
+        let elements = [];
+        SQLITE.forEachInTable(project, "element", (row) => {
+            elements.push([row["rowid"], row["type"]]);
+        });
+        return elements;
+    };
+    // Called by the engine core when the
+    // script debugger breaks (see the
+    // debugger panel's functions below).
+    new_window.self.debuggerBreak = function(report) {
+        return debuggerBreak(report);
+    };
 
     // if the game test window gets closed
     // or refreshed, we set the gameTestWindow
@@ -167,6 +184,72 @@ onpagehide = (e) => {
     }
 };
 
+//////////////////////////////////////
+//          Debugger Panel          //
+
+// The breakpoints the user set, as
+// [element rowid, line] pairs (the
+// state manager's rowid is 0).
+let breakpoints = [];
+// The report of the last time the
+// debugger broke, or undefined.
+let lastReport = undefined;
+
+// This function calls the received function
+// with the engine core exports of the game
+// test window, or tells the user to open
+// the game test window if it isn't open.
+function withEngineCore(fn) {
+    if (gameTestWindow === undefined || gameTestWindow.engineCore === undefined) {
+        DOM.openMessageWindow("The debugger needs the game test window to be open.", () => {});
+        return;
+    }
+    fn(gameTestWindow.engineCore);
+}
+
+// This function writes the breakpoints
+// and the last report into the debugger
+// panel, and shows the panel.
+function renderDebuggerPanel() {
+    const panel = document.querySelector(".debugger-panel");
+    const name = (id) => (id == 0) ? "State Manager" : SQLITE.getRow(project, "element", id).name;
+    let text = "Breakpoints:\n";
+    text += breakpoints.map(([id, line]) => `  ${name(id)}, line ${line}`).join("\n") || "  (none)";
+    if (lastReport !== undefined) {
+        text += `\n\nPaused on ${lastReport.element}, line ${lastReport.line}`;
+        text += `\n\nCall stack:\n${lastReport.call_stack.map((frame) => `  ${frame}`).join("\n")}`;
+        text += "\n\nScope:\n" + Object.entries(lastReport.scope)
+        .map(([variable, value]) => `  ${variable} = ${value}`).join("\n");
+        text += "\n\n" + Object.entries(lastReport.api)
+        .map(([api, value]) => `${api}: ${value}`).join("\n");
+    }
+    panel.querySelector("pre").innerText = text;
+    panel.hidden = false;
+}
+
+// Called by the game test window when the
+// script debugger breaks, with a JSON report
+// of the paused script. The game can't go on
+// until a command is returned, and the page
+// won't be drawn before that, so the report
+// is summarized in a prompt, which picks the
+// command, and the panel keeps the whole
+// report after the game resumes.
+function debuggerBreak(report) {
+    lastReport = JSON.parse(report);
+    renderDebuggerPanel();
+    const scope = Object.entries(lastReport.scope)
+    .map(([variable, value]) => `${variable} = ${value}`).join("\n");
+    const command = window.prompt(`Paused on ${lastReport.element}, line ${lastReport.line}\n\n${scope}\n\n`
+    + "continue / step-into / step-over / step-out / detach", "continue");
+    return ((command === null) ? "continue" : command);
+}
+
+// The debugger panel's close button hides it.
+document.querySelector(".debugger-panel > header > button").onclick = () => {
+    document.querySelector(".debugger-panel").hidden = true;
+};
+
 // Encodes test into binary
 const textEncoder = new TextEncoder();
 // Decodes binary into text
@@ -629,6 +712,52 @@ initSQLite().then((loaded) => {
                 a.click();
                 // We revoke the URL after the download.
                 URL.revokeObjectURL(a.href);
+            },
+            // These functions control the script
+            // debugger of the game test window.
+            'debugger-attach': () => {
+                withEngineCore((engineCore) => {
+                    // The breakpoints might have been set
+                    // before the game test window was opened.
+                    engineCore.debuggerClearBreakpoints();
+                    breakpoints.forEach(([id, line]) => engineCore.debuggerSetBreakpoint(id, line));
+                    engineCore.debuggerAttach();
+                });
+            },
+            'debugger-pause': () => {
+                withEngineCore((engineCore) => engineCore.debuggerPause());
+            },
+            'debugger-detach': () => {
+                withEngineCore((engineCore) => engineCore.debuggerDetach());
+            },
+            // This function toggles a breakpoint
+            // on the line of the script editor's
+            // cursor, in the script of the
+            // selected tab's element.
+            'debugger-breakpoint': () => {
+                const curTab = document.querySelector("#tabSelected");
+                if (curTab.dataset['table'] != "metadata" && curTab.dataset['table'] != "element") {
+                    return;
+                }
+                // the state manager's rowid is 0
+                const id = (curTab.dataset['table'] == "metadata") ? 0 : Number(curTab.dataset['tableId']);
+                const line = DOM.getScriptCursorLine();
+                const idx = breakpoints.findIndex(([bpId, bpLine]) => bpId == id && bpLine == line);
+                if (idx == -1) {
+                    breakpoints.push([id, line]);
+                } else {
+                    breakpoints.splice(idx, 1);
+                }
+                renderDebuggerPanel();
+                // The game test window gets the
+                // breakpoint right away if it's open.
+                if (gameTestWindow !== undefined && gameTestWindow.engineCore !== undefined) {
+                    if (idx == -1) {
+                        gameTestWindow.engineCore.debuggerSetBreakpoint(id, line);
+                    } else {
+                        gameTestWindow.engineCore.debuggerRemoveBreakpoint(id, line);
+                    }
+                }
             }
         }, {
 
@@ -918,6 +1047,11 @@ initSQLite().then((loaded) => {
                 // in the list
                 const curTab = document.querySelector("#tabSelected");
                 if (curTab.dataset['table'] == "metadata") {
+                    // the state manager is reloaded
+                    // using the rowid 0
+                    if (!elementsToLoad.some(([id, _]) => id == 0)) {
+                        elementsToLoad.push([0, 0]);
+                    }
                     return;
                 }
                 if (curTab.dataset['table'] == "element") {
@@ -951,6 +1085,11 @@ initSQLite().then((loaded) => {
                 // in the list
                 const curTab = document.querySelector("#tabSelected");
                 if (curTab.dataset['table'] == "metadata") {
+                    // the state manager is reloaded
+                    // using the rowid 0
+                    if (!elementsToLoad.some(([id, _]) => id == 0)) {
+                        elementsToLoad.push([0, 0]);
+                    }
                     return;
                 }
                 if (curTab.dataset['table'] == "element") {
@@ -5,6 +5,9 @@ opener.self.setupTestWindow(window);
 //
 import initEngine, * as wasmEngine from "../engine-core/game_engine.js";
 
+// The editor's debugger panel
+// uses the engine core's exports.
+self.engineCore = wasmEngine;
 //
 document.addEventListener("contextmenu", (event) => event.preventDefault(), true);
 //
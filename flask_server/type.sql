@@ -6,6 +6,9 @@ DROP TABLE IF EXISTS blobs;
 CREATE TABLE blobs( data blob);
 INSERT INTO elementType VALUES ('object', 1, 2);
 INSERT INTO elementType VALUES ('scene', 3, 4);
+INSERT INTO elementType VALUES ('behavior', 8, 9);
+INSERT INTO elementType VALUES ('library', 10, 11);
+INSERT INTO elementType VALUES ('test', 12, 13);
 INSERT INTO assetType VALUES ('sprite', 'png', 5);
 INSERT INTO assetType VALUES ('audio', 'ogg', 6);
 INSERT INTO assetType VALUES ('font', 'ttf', 7);
\ No newline at end of file
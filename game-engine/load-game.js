@@ -148,6 +148,31 @@ globalThis.elementsToLoad = function() {
     elementsToLoad = [];
     return toLoad;
 };
+// Gives a list of the rowids and types
+// of all the elements in the game, which
+// the engine core's test runner loads
+// into a fresh copy of the game.
+globalThis.allElements = function() {
+    let elements = [];
+    forEachInTable("element", (row) => {
+        elements.push([row["rowid"], row["type"]]);
+    });
+    return elements;
+};
+
+// Called by the engine core when the
+// script debugger breaks, with a JSON
+// report of the paused script. It has to
+// return the command which resumes the
+// script synchronously, so the user is
+// asked through a prompt (the editor's
+// game test window has it's own handler,
+// which feeds the editor's debugger panel).
+globalThis.debuggerBreak = function(report) {
+    const command = window.prompt(`Script paused:\n${report}\n\n`
+    + "continue / step-into / step-over / step-out / detach", "continue");
+    return ((command === null) ? "continue" : command);
+};
 
 // this function should be used to initiate
 // all SQLite database loading functionality
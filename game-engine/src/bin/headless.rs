@@ -0,0 +1,12 @@
+/// Runs the engine without a browser (see
+/// `game_engine::headless`).
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(game_engine::headless::run(&args));
+}
+
+/// The headless runner can't run in
+/// a browser, so it does nothing there.
+#[cfg(target_arch = "wasm32")]
+fn main() {}
@@ -1,46 +1,264 @@
+#[cfg(target_arch = "wasm32")]
+pub use js::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
 
-use wasm_bindgen::prelude::*;
+/// The data getters of the browser, which
+/// are defined by the page the game runs in\
+/// (see `load-game.js` and the editor).
+#[cfg(target_arch = "wasm32")]
+mod js {
+    use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
-extern "C" {
-    // data/script getters
+    #[wasm_bindgen]
+    extern "C" {
+        // data/script getters
+
+        // #[wasm_bindgen(js_name=getMetadataIcon)]
+        // pub fn get_metadata_icon() -> Box<[u8]>;// table = 'blobs', rowid = 3
+        #[wasm_bindgen(js_name=getMetadataScript)]
+        pub fn get_metadata_script() -> String;// table = 'blobs', rowid = 2
+        #[wasm_bindgen(js_name=getAssetData)]
+        pub fn get_asset_data(rowid: u32) -> Box<[u8]>;
+        #[wasm_bindgen(js_name=getElementScript)]
+        pub fn get_element_script(rowid: u32) -> String;
+
+        // config getters
+        #[wasm_bindgen(js_name=getMetadataConfig)]
+        pub fn get_metadata_config() -> String;// table = 'blobs', rowid = 1
+        #[wasm_bindgen(js_name=getAssetConfig)]
+        pub fn get_asset_config(rowid: u32) -> String;
+        #[wasm_bindgen(js_name=getElementConfig)]
+        pub fn get_element_config(rowid: u32) -> String;
+
+        // id to name and vice versa
+        #[wasm_bindgen(js_name=getElementID)]
+        pub fn get_element_id(name: &str) -> u32;
+        #[wasm_bindgen(js_name=getAssetID)]
+        pub fn get_asset_id(name: &str) -> u32;
+        #[wasm_bindgen(js_name=getElementName)]
+        pub fn get_element_name(id: u32) -> String;
+        #[wasm_bindgen(js_name=getAssetName)]
+        pub fn get_asset_name(id: u32) -> String;
+
+        // type getters
+        #[wasm_bindgen(js_name=getElementType)]
+        pub fn get_element_type(id: u32) -> u8;
+        // #[wasm_bindgen(js_name=getAssetType)]
+        // pub fn get_asset_type(id: u32) -> u8;
+
+        // IDs to load getters
+        #[wasm_bindgen(js_name=assetsToLoad)]
+        fn assets_to_load_js() -> Box<[JsValue]>;
+        // The state manager is listed as [0, 0].
+        #[wasm_bindgen(js_name=elementsToLoad)]
+        fn elements_to_load_js() -> Box<[JsValue]>;
+        // Unlike `elements_to_load`, this
+        // doesn't consume any list.
+        #[wasm_bindgen(js_name=allElements)]
+        fn all_elements_js() -> Box<[JsValue]>;
+    }
+
+    /// Reads a list of [rowid, type] arrays.
+    fn rows(list: &[JsValue]) -> Vec<(u32, u8)> {
+        list.iter().map(|row| {
+            let field = |idx| js_sys::Reflect::get_u32(row, idx)
+            .expect("The returned JSValue should be a array with two numbers.").as_f64()
+            .expect("The returned JSValue should be a array with two numbers.");
+            (field(0) as u32, field(1) as u8)
+        }).collect()
+    }
+
+    /// Returns the rowids and types of the
+    /// assets which need to be loaded.
+    pub fn assets_to_load() -> Vec<(u32, u8)> { rows(&assets_to_load_js()) }
+    /// Returns the rowids and types of the
+    /// elements which need to be loaded.
+    pub fn elements_to_load() -> Vec<(u32, u8)> { rows(&elements_to_load_js()) }
+    /// Returns the rowids and types
+    /// of every element in the game.
+    pub fn all_elements() -> Vec<(u32, u8)> { rows(&all_elements_js()) }
+}
+
+/// The same data getters, for the headless
+/// runner, which reads them from a project\
+/// file (or game data file) using SQLite.
+///
+/// Every element and asset is listed to be
+/// loaded once the project is opened, like\
+/// the page does when the game starts.
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::cell::RefCell;
+
+    use rusqlite::{Connection, OptionalExtension};
+
+    thread_local! {
+        /// The open project, and the elements
+        /// and assets which weren't loaded yet.
+        static PROJECT: RefCell<Option<Project>> = const { RefCell::new(None) };
+    }
 
-    // #[wasm_bindgen(js_name=getMetadataIcon)]
-    // pub fn get_metadata_icon() -> Box<[u8]>;// table = 'blobs', rowid = 3
-    #[wasm_bindgen(js_name=getMetadataScript)]
-    pub fn get_metadata_script() -> String;// table = 'blobs', rowid = 2
-    #[wasm_bindgen(js_name=getAssetData)]
-    pub fn get_asset_data(rowid: u32) -> Box<[u8]>;
-    #[wasm_bindgen(js_name=getElementScript)]
-    pub fn get_element_script(rowid: u32) -> String;
+    struct Project {
+        connection: Connection,
+        assets_to_load: Vec<(u32, u8)>,
+        elements_to_load: Vec<(u32, u8)>,
+    }
+
+    /// Opens the project file at the given
+    /// path, which the getters read from.
+    pub fn open_project(path: &str) -> Result<(), String> {
+        let connection = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|err| format!("Couldn't open the project file '{}': {}", path, err))?;
+        use_project(connection)
+    }
+
+    /// Makes the getters read from an open
+    /// connection to a project database.
+    pub fn use_project(connection: Connection) -> Result<(), String> {
+        let mut project = Project { connection, assets_to_load: Vec::new(), elements_to_load: Vec::new() };
+        project.assets_to_load = rows(&project.connection, "SELECT rowid, type FROM asset;")?;
+        project.elements_to_load = rows(&project.connection, "SELECT rowid, type FROM element;")?;
+        PROJECT.set(Some(project));
+        Ok(())
+    }
+
+    /// Opens an in-memory project, with the given
+    /// state manager config and script, and the\
+    /// given elements (name, type, config and
+    /// script), whose rowids start from 1.
+    ///
+    /// It lets the unit tests run games without
+    /// a project file.
+    #[cfg(test)]
+    pub fn use_test_project(config: &str, script: &str, elements: &[(&str, u8, &str, &str)]) {
+        let connection = Connection::open_in_memory().expect("An in-memory database should open.");
+        connection.execute_batch(concat!(
+            "CREATE TABLE asset(rowid INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT, type INTEGER, ",
+            "container INTEGER, config INTEGER, data INTEGER);",
+            "CREATE TABLE element(rowid INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT, type INTEGER, ",
+            "container INTEGER, config INTEGER, script INTEGER);",
+            "CREATE TABLE blobs(rowid INTEGER PRIMARY KEY AUTOINCREMENT, data BLOB);",
+        )).expect("The project's tables should be created.");
+        let insert_blob = |data: &str| -> i64 {
+            connection.execute("INSERT INTO blobs(data) VALUES (?);", [data.as_bytes()])
+            .expect("A blob should be inserted.");
+            connection.last_insert_rowid()
+        };
+        insert_blob(config);
+        insert_blob(script);
+        for &(name, kind, config, script) in elements {
+            let (config, script) = (insert_blob(config), insert_blob(script));
+            connection.execute("INSERT INTO element(name, type, container, config, script) VALUES (?, ?, 0, ?, ?);",
+            rusqlite::params![name, kind, config, script]).expect("An element should be inserted.");
+        }
+        use_project(connection).expect("The test project should be used.");
+    }
+
+    /// Changes the script (and the config, if it's
+    /// given) of an element of the in-memory\
+    /// project (the state manager's if the rowid
+    /// is 0), and lists it to be loaded again,\
+    /// like the editor does.
+    #[cfg(test)]
+    pub fn update_test_element(rowid: u32, config: Option<&str>, script: &str) {
+        PROJECT.with_borrow_mut(|project| {
+            let project = project.as_mut().expect("A test project should be used.");
+            let (config_query, script_query) = if rowid == 0 {
+                ("UPDATE blobs SET data=?1 WHERE rowid=1 AND ?2=0;", "UPDATE blobs SET data=?1 WHERE rowid=2 AND ?2=0;")
+            } else {
+                ("UPDATE blobs SET data=?1 WHERE rowid=(SELECT config FROM element WHERE rowid=?2);",
+                "UPDATE blobs SET data=?1 WHERE rowid=(SELECT script FROM element WHERE rowid=?2);")
+            };
+            for (query, data) in [(config_query, config), (script_query, Some(script))] {
+                let Some(data) = data else { continue; };
+                project.connection.execute(query, rusqlite::params![data.as_bytes(), rowid])
+                .expect("The element should be updated.");
+            }
+            project.elements_to_load.push((rowid, get_type(&project.connection, rowid)));
+        });
+    }
+
+    #[cfg(test)]
+    fn get_type(connection: &Connection, rowid: u32) -> u8 {
+        connection.query_row("SELECT type FROM element WHERE rowid=?;", [rowid], |row| row.get(0))
+        .optional().expect("The project should have an element table.").unwrap_or(0)
+    }
+
+    fn rows(connection: &Connection, query: &str) -> Result<Vec<(u32, u8)>, String> {
+        let mut statement = connection.prepare(query).map_err(|err| err.to_string())?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|err| err.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|err| err.to_string())
+    }
+
+    /// Runs a query which returns a single
+    /// value, or `None` if there's no row.
+    fn query<T: rusqlite::types::FromSql>(query: &str, param: impl rusqlite::ToSql) -> Option<T> {
+        PROJECT.with_borrow(|project| project.as_ref()
+        .expect("A project should be opened before the game's data is read.").connection
+        .query_row(query, [param], |row| row.get(0)).optional()
+        .expect("The project file should have the tables of a project."))
+    }
+
+    fn blob(query_text: &str, rowid: u32) -> Vec<u8> {
+        query(query_text, rowid).unwrap_or_default()
+    }
+
+    fn text(query_text: &str, rowid: u32) -> String {
+        String::from_utf8_lossy(&blob(query_text, rowid)).into_owned()
+    }
+
+    // data/script getters
+    pub fn get_metadata_script() -> String { text("SELECT data FROM blobs WHERE rowid=?;", 2) }
+    pub fn get_asset_data(rowid: u32) -> Box<[u8]> {
+        blob("SELECT blobs.data FROM asset JOIN blobs ON blobs.rowid=asset.data WHERE asset.rowid=?;", rowid)
+        .into_boxed_slice()
+    }
+    pub fn get_element_script(rowid: u32) -> String {
+        text("SELECT blobs.data FROM element JOIN blobs ON blobs.rowid=element.script WHERE element.rowid=?;", rowid)
+    }
 
     // config getters
-    #[wasm_bindgen(js_name=getMetadataConfig)]
-    pub fn get_metadata_config() -> String;// table = 'blobs', rowid = 1
-    #[wasm_bindgen(js_name=getAssetConfig)]
-    pub fn get_asset_config(rowid: u32) -> String;
-    #[wasm_bindgen(js_name=getElementConfig)]
-    pub fn get_element_config(rowid: u32) -> String;
+    pub fn get_metadata_config() -> String { text("SELECT data FROM blobs WHERE rowid=?;", 1) }
+    pub fn get_asset_config(rowid: u32) -> String {
+        text("SELECT blobs.data FROM asset JOIN blobs ON blobs.rowid=asset.config WHERE asset.rowid=?;", rowid)
+    }
+    pub fn get_element_config(rowid: u32) -> String {
+        text("SELECT blobs.data FROM element JOIN blobs ON blobs.rowid=element.config WHERE element.rowid=?;", rowid)
+    }
 
     // id to name and vice versa
-    #[wasm_bindgen(js_name=getElementID)]
-    pub fn get_element_id(name: &str) -> u32;
-    #[wasm_bindgen(js_name=getAssetID)]
-    pub fn get_asset_id(name: &str) -> u32;
-    #[wasm_bindgen(js_name=getElementName)]
-    pub fn get_element_name(id: u32) -> String;
-    #[wasm_bindgen(js_name=getAssetName)]
-    pub fn get_asset_name(id: u32) -> String;
+    pub fn get_element_id(name: &str) -> u32 {
+        query("SELECT rowid FROM element WHERE name=?;", name).unwrap_or(0)
+    }
+    pub fn get_asset_id(name: &str) -> u32 {
+        query("SELECT rowid FROM asset WHERE name=?;", name).unwrap_or(0)
+    }
+    pub fn get_element_name(id: u32) -> String {
+        query("SELECT name FROM element WHERE rowid=?;", id).unwrap_or_default()
+    }
+    pub fn get_asset_name(id: u32) -> String {
+        query("SELECT name FROM asset WHERE rowid=?;", id).unwrap_or_default()
+    }
 
     // type getters
-    #[wasm_bindgen(js_name=getElementType)]
-    pub fn get_element_type(id: u32) -> u8;
-    // #[wasm_bindgen(js_name=getAssetType)]
-    // pub fn get_asset_type(id: u32) -> u8;
+    pub fn get_element_type(id: u32) -> u8 {
+        query("SELECT type FROM element WHERE rowid=?;", id).unwrap_or(0)
+    }
 
     // IDs to load getters
-    #[wasm_bindgen(js_name=assetsToLoad)]
-    pub fn assets_to_load() -> Box<[JsValue]>;
-    #[wasm_bindgen(js_name=elementsToLoad)]
-    pub fn elements_to_load() -> Box<[JsValue]>;
-}
\ No newline at end of file
+    pub fn assets_to_load() -> Vec<(u32, u8)> {
+        PROJECT.with_borrow_mut(|project| project.as_mut()
+        .map(|project| std::mem::take(&mut project.assets_to_load)).unwrap_or_default())
+    }
+    pub fn elements_to_load() -> Vec<(u32, u8)> {
+        PROJECT.with_borrow_mut(|project| project.as_mut()
+        .map(|project| std::mem::take(&mut project.elements_to_load)).unwrap_or_default())
+    }
+    pub fn all_elements() -> Vec<(u32, u8)> {
+        PROJECT.with_borrow(|project| project.as_ref()
+        .map(|project| rows(&project.connection, "SELECT rowid, type FROM element;")
+        .expect("The project file should have the tables of a project.")).unwrap_or_default())
+    }
+}
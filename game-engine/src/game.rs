@@ -9,7 +9,7 @@ use crate::data;
 
 /// Defines the game engine's API,
 /// And all of it's associated types.
-mod engine_api;
+pub(crate) mod engine_api;
 /// Defines the game's renderer.
 mod renderer;
 
@@ -43,7 +43,7 @@ extern "C" {
 /// stored in the 'element' table, but in
 /// unique rows in the 'blobs' table,\
 /// refered to in this code base as `Metadata`.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TableRow {
     Metadata,
     Element(u32, u8),
@@ -62,15 +62,22 @@ impl TableRow {
     /// string, which will contain the name\
     /// of the element or asset, which caused
     /// the error.
-    pub fn to_err_string(&self, err: &str) -> String {
-        let self_str = match self.clone() {
-            Self::Metadata => String::from("\non 'State Manager'."),
-            Self::Element(id, kind) => format!("\non the '{}' {kind_str}.", data::get_element_name(id.clone()),
-            kind_str = match kind { 1 => "object", 2 => "scene", _ => "element" }),
-            Self::Asset(id, kind) => format!("\non the '{}' {kind_str}.", data::get_element_name(id.clone()),
+    pub fn to_err_string(self, err: &str) -> String {
+        format!("{}\non {}.", err, self.describe())
+    }
+
+    /// Describes the element or asset
+    /// in a short string, which contains\
+    /// it's name and kind, like "the 'Player'
+    /// object" or "'State Manager'".
+    pub fn describe(&self) -> String {
+        match *self {
+            Self::Metadata => String::from("'State Manager'"),
+            Self::Element(id, kind) => format!("the '{}' {kind_str}", data::get_element_name(id),
+            kind_str = match kind { 1 => "object", 2 => "scene", 3 => "behavior", 4 => "library", 5 => "test", _ => "element" }),
+            Self::Asset(id, kind) => format!("the '{}' {kind_str}", data::get_asset_name(id),
             kind_str = match kind { 1 => "sprite", 2 => "audio", 3 => "font", _ => "asset" }),
-        };
-        format!("{}{}", err, self_str)
+        }
     }
 }
 
@@ -94,7 +101,7 @@ impl TableRow {
 /// 
 /// # Examples
 /// 
-/// ```rust
+/// ```rust,ignore
 /// let dynamic = rhai::Dynamic::from(1);
 /// let i: u8 = dynamic_to_number(&dynamic)? as u8;
 /// assert_eq!(i, 1_u8);
@@ -106,7 +113,7 @@ pub fn dynamic_to_number(dynam: &rhai::Dynamic) -> Result<f32, &str> {
     if dynam.is_int() { 
         return Ok(dynam.as_int()? as f32);
     }
-    Ok(dynam.as_float()? as f32)
+    dynam.as_float()
 }
 
 /// This function will load all the assets,
@@ -119,17 +126,10 @@ fn load_assets(engine: &Engine, asset_defs: &mut renderer::AssetDefinitions,
 gl_context: &web_sys::WebGlRenderingContext) {
     // Gets the array of assets to load
     // from the data module and iterates over it.
-    for asset in data::assets_to_load().iter() {
-        // Gets the asset's id and type from the array.
-        let int_id = js_sys::Reflect::get_u32(&asset, 0)
-        .expect("The returned JSValue should be a array with two numbers.").as_f64()
-        .expect("The returned JSValue should be a array with two numbers.") as u32;
-        let int_type = js_sys::Reflect::get_u32(&asset, 1)
-        .expect("The returned JSValue should be a array with two numbers.").as_f64()
-        .expect("The returned JSValue should be a array with two numbers.") as u8;
+    for (int_id, int_type) in data::assets_to_load() {
         // Creates and inserts the asset into the table.
         // If it already there, it will be overwritten.
-        asset_defs.insert(int_id, renderer::AssetDefinition::new(&engine,
+        asset_defs.insert(int_id, renderer::AssetDefinition::new(engine,
         TableRow::Asset(int_id, int_type), gl_context));
     }
 }
@@ -143,25 +143,83 @@ gl_context: &web_sys::WebGlRenderingContext) {
 /// However, if the `init` parameter is set to
 /// `true`, it will only load the elements, which\
 /// were not already loaded.
-fn load_elements(engine: &Engine, element_defs: &mut engine_api::ElementDefinitions, init: bool) {
+/// 
+/// Libraries are resolved using the received
+/// `LibraryResolver`, which forgets every\
+/// library that is loaded again, so it
+/// will be compiled again when it's imported.
+/// 
+/// If an element which was already loaded
+/// can't be loaded again (because of a\
+/// compile error for example), the error is
+/// reported to the console, and the old\
+/// definition is kept, so the game will keep
+/// running the old version of the element.
+/// 
+/// Returns the rowids of the elements which
+/// were loaded or updated successfully.
+fn load_elements(engine: &Engine, libraries: &engine_api::LibraryResolver,
+element_defs: &mut engine_api::ElementDefinitions, init: bool) -> Vec<u32> {
+    // Keeps track of the elements which
+    // were loaded or updated in this call.
+    let mut loaded: Vec<u32> = Vec::new();
     // Gets the array of elements to load
-    // from the data module and iterates over it.
-    for element in data::elements_to_load().iter() {
-        // Gets the element's id and type from the array.
-        let int_id = js_sys::Reflect::get_u32(&element, 0)
-        .expect("The returned JSValue should be a array with two numbers.").as_f64()
-        .expect("The returned JSValue should be a array with two numbers.") as u32;
-        let int_type = js_sys::Reflect::get_u32(&element, 1)
-        .expect("The returned JSValue should be a array with two numbers.").as_f64()
-        .expect("The returned JSValue should be a array with two numbers.") as u8;
+    // from the data module and collects their
+    // ids and types, so updated libraries can
+    // be forgotten before anything imports them.
+    let mut to_load: Vec<(u32, u8)> = Vec::new();
+    for (int_id, int_type) in data::elements_to_load() {
         // If the element is already loaded, and the init
         // parameter is set to true, it will skip the element.
         if element_defs.contains_key(&int_id) && init { continue; }
+        if int_type == 4 { libraries.forget(int_id); }
+        to_load.push((int_id, int_type));
+    }
+    for (int_id, int_type) in to_load {
+        // The state manager is listed as [0, 0],
+        // the key of it's definition in the table.
+        let row = if int_id == 0 { TableRow::Metadata } else { TableRow::Element(int_id, int_type) };
         // Creates and inserts the element into the table.
         // If it already there, it will be overwritten.
-        element_defs.insert(int_id, engine_api::ElementDefinition::new(&engine,
-        TableRow::Element(int_id, int_type)));
+        if insert_element(element_defs, int_id, engine_api::ElementDefinition::new(engine, libraries, row)) {
+            loaded.push(int_id);
+        }
     }
+    // Objects which inherit from an object which
+    // was just updated need to be reloaded too,
+    // because their definitions include the
+    // scripts and configs of their ancestors.
+    // The same goes for elements which import
+    // a library which was just updated.
+    let descendants: Vec<(u32, TableRow)> = element_defs.iter()
+    .filter_map(|(&id, def)| def.as_ref().ok().map(|def| (id, def)))
+    .filter(|(id, def)| !loaded.contains(id) &&
+    def.ancestors.iter().chain(def.libraries.iter()).any(|ancestor| loaded.contains(ancestor)))
+    .map(|(id, def)| (id, def.row)).collect();
+    for (id, row) in descendants {
+        if insert_element(element_defs, id, engine_api::ElementDefinition::new(engine, libraries, row)) {
+            loaded.push(id);
+        }
+    }
+    loaded
+}
+
+/// Inserts an element's definition into the
+/// table, unless it couldn't be loaded while\
+/// an older version of it was loaded successfully,
+/// in which case the error is reported to the\
+/// console and the older version is kept.
+/// 
+/// Returns `true` if the definition was inserted.
+fn insert_element(element_defs: &mut engine_api::ElementDefinitions, id: u32,
+definition: Result<Rc<engine_api::ElementDefinition>, String>) -> bool {
+    if let (Err(err), Some(Ok(_))) = (&definition, element_defs.get(&id)) {
+        web_sys::console::error_1(&JsValue::from_str(&format!(
+        "{}\nThe previous version will keep running until the error is fixed.", err)));
+        return false;
+    }
+    element_defs.insert(id, definition);
+    true
 }
 
 /// This struct is used to track
@@ -282,6 +340,7 @@ pub struct Game {
     key_tracker: Option<KeyStateTracker>,
     webgl_renderer: Option<renderer::WebGlRenderer>,
     element_defs: Rc<RefCell<engine_api::ElementDefinitions>>,
+    libraries: Rc<engine_api::LibraryResolver>,
     asset_defs: Option<renderer::AssetDefinitions>,
 }
 
@@ -295,6 +354,9 @@ impl Game {
         // Create the element definitions table.
         let element_defs: Rc<RefCell<engine_api::ElementDefinitions>> 
             = Rc::new(RefCell::new(HashMap::new()));
+        // Create the library resolver, which
+        // will cache the compiled libraries.
+        let libraries = Rc::new(engine_api::LibraryResolver::new());
         // Create the asset definitions table.
         let mut asset_defs: renderer::AssetDefinitions = HashMap::new();
         // Create the game engine's API in
@@ -302,7 +364,7 @@ impl Game {
         // the components which are integrated
         // with the API.
         let (engine_api, game_elements,
-        key_states) = engine_api::create_api(&element_defs)?;
+        key_states) = engine_api::create_api(&element_defs, &libraries)?;
         // Create the key state tracker
         // using the key states table,
         // which is already integrated
@@ -310,7 +372,7 @@ impl Game {
         let key_tracker = KeyStateTracker::new(key_states)?;
         // Load all the elements which
         // were not already loaded.
-        load_elements(&engine_api, &mut element_defs.borrow_mut(), true);
+        load_elements(&engine_api, &libraries, &mut element_defs.borrow_mut(), true);
         // Create the WebGL renderer
         // the game will use to render
         // it's graphics.
@@ -319,9 +381,13 @@ impl Game {
         .read_lock::<engine_api::element::Game>()
         .expect("read_lock cast should succeed"))?;
         // Load all the assets
-        load_assets(&engine_api, &mut asset_defs, &webgl_renderer.gl_context);
+        engine_api::profiler::measure(None, "load_assets",
+        || load_assets(&engine_api, &mut asset_defs, &webgl_renderer.gl_context));
         // Call the `init` function on all the elements.
         game_elements.call_fn_on_all("init", (), &engine_api)?;
+        // Let the host page's console evaluate
+        // code in the context of the elements.
+        engine_api::repl::install(&engine_api, &game_elements);
         // Return the new `Game`.
         Ok(Self {
             engine_api,
@@ -329,6 +395,7 @@ impl Game {
             key_tracker: Some(key_tracker),
             webgl_renderer: Some(webgl_renderer),
             element_defs,
+            libraries,
             asset_defs: Some(asset_defs),
         })
     }
@@ -385,10 +452,10 @@ impl Game {
             // Load all the assets which
             // were not already loaded, and
             // update the ones which were.
-            load_assets(&engine_api,
-            &mut asset_defs, &webgl_renderer.gl_context);
+            engine_api::profiler::measure(None, "load_assets", || load_assets(&engine_api,
+            &mut asset_defs, &webgl_renderer.gl_context));
             // Render the game's graphics.
-            webgl_renderer.render_scene(
+            engine_api::profiler::measure(None, "render_scene", || webgl_renderer.render_scene(
                 &game_elements.state_manager.properties
                 .borrow().read_lock::<engine_api::element::Game>()
                 .expect("read_lock cast should succeed"),
@@ -397,7 +464,7 @@ impl Game {
                 .expect("read_lock cast should succeed"),
                 &game_elements.object_stack.borrow(),
                 &asset_defs, elapsed
-            )?;
+            ))?;
             // Request the next frame.
             window().unwrap().request_animation_frame(
                 draw_loop
@@ -429,10 +496,12 @@ impl Game {
         .ok_or(JsValue::from_str("Tried to start the main loop a second time."))?;
         // Take a reference to the
         // game elements, rhai API
-        // engine, and element definitions.
+        // engine, element definitions
+        // and library resolver.
         let game_elements = Rc::clone(&self.game_elements);
         let engine_api = Rc::clone(&self.engine_api);
         let element_defs = Rc::clone(&self.element_defs);
+        let libraries = Rc::clone(&self.libraries);
         
         // Set up the update loop:
 
@@ -467,42 +536,9 @@ impl Game {
             let update_time = window().unwrap().performance().unwrap().now();
             let elapsed = update_time - last_update;
             last_update = update_time;
-            // Call the `update` function on all the elements.
-            game_elements.call_fn_on_all("update", (elapsed as rhai::FLOAT, ), &engine_api)?;
-            // Calibrate the key states.
-            key_tracker.calibrate();
-            // Get the current scene's id.
-            let row_copy = game_elements.cur_scene.resources.borrow().definition.row;
-            if let TableRow::Element(id, 2) = row_copy {
-                // Make a mutable copy of the current scene's id.
-                let mut prv_scene_id = id;
-                // Get the state manager's `cur_scene` property.
-                let mut cur_scene_id = game_elements.state_manager.properties.borrow()
-                .read_lock::<engine_api::element::Game>()
-                .expect("read_lock cast should succeed").cur_scene;
-                // If the current scene's id is different from
-                // what the state manager's `cur_scene` property
-                // implies, switch to the current scene.
-                while cur_scene_id != prv_scene_id {
-                    game_elements.switch_scene(cur_scene_id,
-                    &engine_api, &element_defs.borrow())?;
-                    // Call the `init` function on all the elements.
-                    game_elements.call_fn_on_all("init", (), &engine_api)?;
-                    // Update the previous scene id to
-                    // the scene id we just switched to,
-                    // and get the `cur_scene` property again.
-                    prv_scene_id = cur_scene_id;
-                    cur_scene_id = game_elements.state_manager.properties
-                    .borrow().read_lock::<engine_api::element::Game>()
-                    .expect("read_lock cast should succeed").cur_scene;
-                // If the state manager switched the scene again in the init function,
-                // keep switching the scene until the two values are equal.
-                }
-            }
-            // Load all the elements which
-            // were not already loaded, and
-            // update the ones which were.
-            load_elements(&engine_api, &mut element_defs.borrow_mut(), false);
+            // Run the frame's update phase.
+            game_elements.run_frame(&engine_api, &element_defs, elapsed, || key_tracker.calibrate(),
+            |element_defs| load_elements(&engine_api, &libraries, element_defs, false))?;
             // Request the next frame.
             set_timeout_with_callback_and_f64(
                 update_loop
@@ -527,4 +563,74 @@ impl Game {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+/// The parts of a game which run without a
+/// browser, for the headless runner: there's\
+/// no renderer and no assets, and the keys
+/// are only pressed by the code it evaluates.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct HeadlessGame {
+    engine_api: Rc<rhai::Engine>,
+    game_elements: Rc<engine_api::GameElementSet>,
+    key_states: Rc<RefCell<engine_api::KeyStates>>,
+    element_defs: Rc<RefCell<engine_api::ElementDefinitions>>,
+    libraries: Rc<engine_api::LibraryResolver>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl HeadlessGame {
+    /// Creates a new `HeadlessGame` from the
+    /// open project, the same way `Game::new`\
+    /// does, and lets the console evaluate code
+    /// in the context of it's elements.
+    pub fn new() -> Result<Self, String> {
+        let element_defs: Rc<RefCell<engine_api::ElementDefinitions>> = Rc::new(RefCell::new(HashMap::new()));
+        let libraries = Rc::new(engine_api::LibraryResolver::new());
+        let (engine_api, game_elements, key_states) = engine_api::create_api(&element_defs, &libraries)?;
+        load_elements(&engine_api, &libraries, &mut element_defs.borrow_mut(), true);
+        game_elements.call_fn_on_all("init", (), &engine_api)?;
+        engine_api::repl::install(&engine_api, &game_elements);
+        Ok(Self { engine_api, game_elements, key_states, element_defs, libraries })
+    }
+
+    /// Runs a single frame of the game,
+    /// as long as a frame of the main loop.
+    pub fn step(&self) -> Result<(), String> {
+        let elapsed = 1000_f64 / self.game_elements.state_manager.properties.borrow()
+        .read_lock::<engine_api::element::Game>().expect("read_lock cast should succeed").fps.max(1) as f64;
+        self.game_elements.run_frame(&self.engine_api, &self.element_defs, elapsed, || {
+            for state in self.key_states.borrow_mut().values_mut() {
+                state.just_pressed = false;
+                state.just_released = false;
+            }
+        }, |element_defs| load_elements(&self.engine_api, &self.libraries, element_defs, false))
+    }
+}
+
+/// The state manager config the unit tests'
+/// games use (it has every required attribute).
+#[cfg(test)]
+pub const TEST_CONFIG: &str = r##"{"canvas-width": 960, "canvas-height": 540, "clear-color": "#000000",
+"fps": 60, "version": [1,0,0,0], "browser-title": "Test", "initial-scene": 1}"##;
+/// The config of an empty scene with a single layer.
+#[cfg(test)]
+pub const TEST_SCENE_CONFIG: &str = r##"{"object-instances": [], "layers": ["layer 1"],
+"camera": {"x": 0, "y": 0, "zoom": 1, "color": "#FFFFFF", "alpha": 255}}"##;
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+impl HeadlessGame {
+    /// Starts a game for a unit test, with the
+    /// given state manager script, and the\
+    /// given elements (see `data::use_test_project`),
+    /// the first of which should be a scene.
+    pub fn for_test(state_script: &str, elements: &[(&str, u8, &str, &str)]) -> Self {
+        data::use_test_project(TEST_CONFIG, state_script, elements);
+        Self::new().unwrap_or_else(|err| panic!("The test game should start: {}", err))
+    }
+
+    /// Evaluates code on the state manager,
+    /// and returns the result's description.
+    pub fn eval(&self, code: &str) -> Result<String, String> {
+        engine_api::repl::eval("state-manager", 0, code)
+    }
+}
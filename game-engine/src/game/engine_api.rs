@@ -1,7 +1,6 @@
 
-use std::{collections::HashMap, cell::RefCell, rc::Rc};
+use std::{collections::{HashMap, VecDeque}, cell::{Cell, RefCell}, rc::Rc};
 
-use web_sys::console::log_1;
 use rhai::{Engine, Scope, AST, Map, EvalAltResult, Dynamic,
     packages::{Package, StandardPackage}};
 
@@ -11,9 +10,31 @@ use crate::{data, game::{TableRow, dynamic_to_number}};
 pub mod element;
 /// Defines asset APIs for different asset types
 pub mod asset;
+/// Defines the script debugger, which
+/// is driven by the host page
+pub mod debugger;
+/// Defines the console, which evaluates code
+/// typed into the host page or the headless runner
+pub mod repl;
+/// Defines the log, which stores the
+/// messages logged by the scripts
+pub mod logger;
+/// Defines the profiler, which times
+/// the scripts and the engine
+pub mod profiler;
+/// Defines the test runner, which runs
+/// the game's test elements
+pub mod testing;
+/// Defines the random number generator,
+/// which is seeded by the game
+pub mod random;
 
 pub type ElementDefinitions = HashMap<u32,Result<Rc<ElementDefinition>, String>>;
 pub type KeyStates = HashMap<String, KeyState>;
+pub type MessageQueue = VecDeque<PostedMessage>;
+/// The engine, the game's elements and
+/// the key states `create_api` returns.
+pub type GameApi = (Rc<Engine>, Rc<GameElementSet>, Rc<RefCell<KeyStates>>);
 
 /// A struct that will be
 /// used to track the state\
@@ -25,6 +46,181 @@ pub struct KeyState {
     pub just_released: bool,
 }
 
+/// This enum is used to identify
+/// the element which should receive\
+/// a posted message.
+#[derive(Clone, Copy)]
+pub enum MessageTarget {
+    StateManager,
+    CurScene,
+    Object(u32),
+}
+
+/// A struct that will be used to
+/// store a message, which was posted\
+/// by an element using one of the
+/// `post_*` API functions, until it\
+/// gets delivered to it's target.
+pub struct PostedMessage {
+    pub sender: Option<TableRow>,
+    pub target: MessageTarget,
+    pub name: String,
+    pub args: rhai::Array,
+}
+
+/// Returns the row of the element which posts
+/// a message: the call's tag, if the message is\
+/// posted by a callback, or the running element
+/// otherwise (like when a script's body, or a\
+/// piece of code typed into a console runs).
+fn message_sender(context: &rhai::NativeCallContext) -> Option<TableRow> {
+    context.tag().and_then(|tag| tag.clone().try_cast::<TableRow>())
+    .or_else(|| RUNNING_ELEMENT.get().map(|(row, _)| row))
+}
+
+/// Returns the error of a messaging function
+/// which was called with an index that is\
+/// out of the object stack's bounds.
+fn object_index_error(function: &str, idx: rhai::INT, len: usize) -> Box<EvalAltResult> {
+    format!("Argument 'idx' was out of bounds in call to '{}'.\n\
+    Tried to find index {} on the object stack, when it only had {} elements.", function, idx, len).into()
+}
+
+thread_local! {
+    /// The element which owns the callback
+    /// that is currently running, and the\
+    /// operation budget of the callback, if
+    /// the element overrides the default\
+    /// one (see `ScriptBudget`).
+    static RUNNING_ELEMENT: Cell<Option<(TableRow, Option<u64>)>> = const { Cell::new(None) };
+}
+
+/// The number of operations a single callback
+/// can run, if the state manager's config\
+/// doesn't specify a budget of it's own.
+pub const DEFAULT_MAX_OPERATIONS: u64 = 1_000_000;
+/// The maximum depth of nested function calls.
+pub const DEFAULT_MAX_CALL_LEVELS: usize = 64;
+/// The maximum length of a string.
+pub const DEFAULT_MAX_STRING_SIZE: usize = 65_536;
+/// The maximum length of an array.
+pub const DEFAULT_MAX_ARRAY_SIZE: usize = 65_536;
+/// The maximum number of properties in a map.
+pub const DEFAULT_MAX_MAP_SIZE: usize = 65_536;
+
+/// This struct defines the limits every
+/// callback of a script has to stay within,\
+/// which are read from the optional
+/// `script-budget` attribute of the state\
+/// manager's config.
+///
+/// Without these limits, an accidental
+/// `loop {}` would freeze the page.\
+/// The size and call depth limits are set
+/// on the rhai engine, so they are shared\
+/// by all the elements, while the operation
+/// budget is counted by the engine's progress\
+/// callback, so an element can override it
+/// in it's own `script-budget` attribute.
+#[derive(Clone, Copy)]
+pub struct ScriptBudget {
+    pub max_operations: u64,
+    pub max_call_levels: usize,
+    pub max_string_size: usize,
+    pub max_array_size: usize,
+    pub max_map_size: usize,
+}
+
+impl ScriptBudget {
+    /// Reads the budget from the state manager's
+    /// config, using the default value of every\
+    /// limit it doesn't specify.
+    pub fn new(config: &Map) -> Self {
+        let limit = |name: &str, default: usize| -> usize {
+            budget_attribute(config, name)
+            .expect("The state manager's config 'script-budget' attribute should be an object of integers.")
+            .map_or(default, |limit| limit as usize)
+        };
+        Self {
+            max_operations: limit("max-operations", DEFAULT_MAX_OPERATIONS as usize) as u64,
+            max_call_levels: limit("max-call-levels", DEFAULT_MAX_CALL_LEVELS),
+            max_string_size: limit("max-string-size", DEFAULT_MAX_STRING_SIZE),
+            max_array_size: limit("max-array-size", DEFAULT_MAX_ARRAY_SIZE),
+            max_map_size: limit("max-map-size", DEFAULT_MAX_MAP_SIZE),
+        }
+    }
+
+    /// Sets the limits on the rhai engine, and
+    /// registers a progress callback, which\
+    /// aborts a callback once it runs more
+    /// operations than it's budget allows.
+    ///
+    /// Returns the default operation budget,
+    /// which the progress callback reads, so\
+    /// it can be changed while the game runs
+    /// (see `ScriptBudget::reapply`).
+    pub fn apply(&self, engine: &mut Engine) -> Rc<Cell<u64>> {
+        engine.set_max_call_levels(self.max_call_levels)
+              .set_max_string_size(self.max_string_size)
+              .set_max_array_size(self.max_array_size)
+              .set_max_map_size(self.max_map_size);
+        let default_budget = Rc::new(Cell::new(self.max_operations));
+        let progress_budget = Rc::clone(&default_budget);
+        engine.on_progress(move |operations| {
+            let budget = RUNNING_ELEMENT.get().and_then(|(_, budget)| budget).unwrap_or(progress_budget.get());
+            // The budget is used as the termination
+            // token, so it can be named in the error.
+            (budget != 0 && operations > budget).then(|| Dynamic::from(budget))
+        });
+        default_budget
+    }
+
+    /// Applies the budget to a running game,
+    /// after the state manager was reloaded.
+    ///
+    /// The default operation budget changes right
+    /// away, but the engine is shared by the\
+    /// running game, so it's other limits can't
+    /// change until the game restarts, which is\
+    /// logged if they were changed.
+    pub fn reapply(&self, engine: &Engine, default_budget: &Cell<u64>) {
+        default_budget.set(self.max_operations);
+        if engine.max_call_levels() != self.max_call_levels || engine.max_string_size() != self.max_string_size
+        || engine.max_array_size() != self.max_array_size || engine.max_map_size() != self.max_map_size {
+            logger::log(logger::LogLevel::Warn, concat!("The size and call depth limits of the",
+            " 'script-budget' attribute will only change when the game restarts."));
+        }
+    }
+}
+
+/// Reads a single limit from the optional
+/// `script-budget` attribute of a config.
+fn budget_attribute(config: &Map, name: &str) -> Result<Option<f32>, ()> {
+    let Some(budget) = config.get("script-budget") else { return Ok(None); };
+    let budget = budget.read_lock::<Map>().ok_or(())?;
+    budget.get(name).map(|limit| dynamic_to_number(limit).map_err(|_| ())).transpose()
+}
+
+/// If the error was raised because a callback
+/// ran out of it's operation budget, this\
+/// function will return the budget.
+fn exceeded_budget(err: &EvalAltResult) -> Option<u64> {
+    match err {
+        EvalAltResult::ErrorInFunctionCall(.., inner, _) => exceeded_budget(inner),
+        EvalAltResult::ErrorTerminated(token, _) => token.clone().try_cast::<u64>(),
+        _ => None,
+    }
+}
+
+/// The rowids of the elements which own
+/// functions in a merged script, by name.
+type FnOwners = HashMap<String, u32>;
+
+/// A single object in an inheritance chain:
+/// it's row, script, config and the names\
+/// of the functions it's script defines.
+type ChainLevel = (TableRow, String, Map, Vec<String>);
+
 /// A struct that will be used to
 /// store all the data which is loaded\
 /// for a single element defined in the
@@ -33,6 +229,13 @@ pub struct ElementDefinition {
     pub config: Map,
     pub script: AST,
     pub row: TableRow,
+    pub ancestors: Vec<u32>,
+    pub libraries: Vec<u32>,
+    /// The rowids of the elements which own the
+    /// functions merged into the script (imported\
+    /// from a library or inherited from an ancestor).
+    fn_owners: FnOwners,
+    max_operations: Option<u64>,
 }
 
 impl ElementDefinition {
@@ -40,10 +243,27 @@ impl ElementDefinition {
     /// this function will load the element's\
     /// configuration and script and return
     /// a new element definition, or an error.
-    pub fn new(engine: &Engine, row: TableRow) -> Result<Rc<Self>, String> {
+    ///
+    /// The libraries imported by the element
+    /// are resolved using the given resolver.
+    pub fn new(engine: &Engine, libraries: &LibraryResolver, row: TableRow) -> Result<Rc<Self>, String> {
+        // A library is compiled by the resolver,
+        // so it will only be compiled once.
+        if let TableRow::Element(rowid, 4) = row {
+            let library = libraries.resolve(engine, rowid)?;
+            return Ok(Rc::new(Self {
+                config: Map::new(),
+                script: library.script.clone(),
+                row,
+                ancestors: Vec::new(),
+                libraries: library.imports.clone(),
+                fn_owners: HashMap::new(),
+                max_operations: None,
+            }));
+        }
         // Load the element's script and compile
         // it into an AST (Abstract Syntax Tree).
-        let ast = engine.compile(&match row {
+        let source = match row {
             // The metadata script is the state manager's script.
             TableRow::Metadata => data::get_metadata_script(),
             // An element script is a script associated with
@@ -54,7 +274,8 @@ impl ElementDefinition {
             TableRow::Asset(rowid, type_num) => { return Err(
             format!("Can't define an asset as an element (on ElementDefinition::new())(name: '{}', id: {}, type: {})",
             data::get_asset_name(rowid), rowid, type_num)); },
-        });
+        };
+        let ast = engine.compile(&source);
         // Return a parse error if any occured
         // while compiling the script.
         if let Some(err) = ast.as_ref().err() {
@@ -79,6 +300,32 @@ impl ElementDefinition {
         if let Some(err) = json.as_ref().err() {
             return Err(row.to_err_string(&err.to_string()));
         }
+        let mut config = json.unwrap();
+        // Check the colors in the config, so
+        // a malformed one is reported here.
+        element::validate_config_colors(&config).map_err(|err| row.to_err_string(&err))?;
+        let mut ast = ast.unwrap();
+        let mut ancestors = Vec::new();
+        let mut inherited_fns = HashMap::new();
+        // If the element is an object, which names
+        // a parent object in it's config, resolve
+        // the inheritance chain.
+        if let (TableRow::Element(_, 1), true) = (row, config.contains_key("parent")) {
+            (ast, ancestors, inherited_fns) = Self::inherit(engine, row, &source, &ast, &mut config)?;
+        }
+        // Merge the functions of the libraries
+        // the element imports into it's AST.
+        let (ast, imports, mut fn_owners) = libraries.import(engine, row, &config, ast)?;
+        fn_owners.extend(inherited_fns);
+        // An element can override the operation
+        // budget of it's callbacks (the state
+        // manager's budget is the default one).
+        let max_operations = match row {
+            TableRow::Metadata => None,
+            _ => budget_attribute(&config, "max-operations").map_err(|_| row.to_err_string(
+            "The 'script-budget' attribute of an element's config should be an object of integers."))?
+            .map(|limit| limit as u64),
+        };
         // Return a new element definition
         // wrapped in a counted reference
         // (if no error occured while loading
@@ -88,11 +335,428 @@ impl ElementDefinition {
         // use it to access the element's
         // configuration and script).
         Ok(Rc::new(Self {
-            config: json.unwrap(), 
-            script: ast.unwrap(),
+            config, 
+            script: ast,
             row,
+            ancestors,
+            libraries: imports,
+            fn_owners,
+            max_operations,
         }))
     }
+
+    /// Turns an error, which was raised while
+    /// running one of the element's callbacks\
+    /// (described by `callback`), into an error
+    /// message. Errors raised inside a function,
+    /// which was imported from a library (or\
+    /// inherited from an ancestor), will name
+    /// the element which owns it.
+    pub fn err_string(&self, callback: &str, err: EvalAltResult) -> String {
+        let message = match exceeded_budget(&err) {
+            Some(budget) => format!("The {} was aborted, because it exceeded it's budget of {} operations.\n{}",
+            callback, budget, self.name_libraries(err)),
+            None => self.name_libraries(err).to_string(),
+        };
+        self.row.to_err_string(&message)
+    }
+
+    /// Returns the rowid of the element which
+    /// owns a function in the element's script\
+    /// (the state manager's rowid is 0).
+    pub fn fn_owner(&self, name: &str) -> u32 {
+        match (self.fn_owners.get(name), self.row) {
+            (Some(&id), _) | (None, TableRow::Element(id, _)) => id,
+            _ => 0,
+        }
+    }
+
+    /// Sets the source of every call to an
+    /// imported (or inherited) function in the\
+    /// error's call stack to the name of it's
+    /// owner, so the error's position will be\
+    /// understood as a position in it's script.
+    fn name_libraries(&self, err: EvalAltResult) -> EvalAltResult {
+        match err {
+            EvalAltResult::ErrorInFunctionCall(name, source, inner, pos) => {
+                let source = match self.fn_owners.get(&name) {
+                    Some(&id) if source.is_empty() => data::get_element_name(id),
+                    _ => source,
+                };
+                EvalAltResult::ErrorInFunctionCall(name, source, Box::new(self.name_libraries(*inner)), pos)
+            },
+            err => err,
+        }
+    }
+
+    /// Resolves the inheritance chain of an
+    /// object, which names a parent object\
+    /// in it's config, and returns the merged
+    /// AST, the rowids of the ancestors and\
+    /// the owners of the inherited functions.
+    ///
+    /// The object inherits the `sprites` list
+    /// (if it doesn't have one of it's own),\
+    /// the default `properties` and the script
+    /// of every ancestor. Functions defined in\
+    /// the object's script override the ones
+    /// defined by it's ancestors, which can\
+    /// still be called by adding the `super_`
+    /// prefix to their name (`super_update!(elapsed)`).
+    fn inherit(engine: &Engine, row: TableRow, source: &str, ast: &AST, config: &mut Map)
+     -> Result<(AST, Vec<u32>, FnOwners), String> {
+        // Collect the scripts, configs and
+        // function names of every object in
+        // the chain, starting from the object.
+        let mut chain: Vec<ChainLevel> = vec![(row, String::from(source), config.clone(),
+        ast.iter_functions().map(|func| String::from(func.name)).collect())];
+        let mut ancestors: Vec<u32> = Vec::new();
+        while let Some(parent) = chain.last().expect("the chain can't be empty").2.get("parent") {
+            let child_row = chain.last().expect("the chain can't be empty").0;
+            let parent_id = dynamic_to_number(parent)
+            .map_err(|_| child_row.to_err_string("The 'parent' attribute of an object's config should be an integer."))? as u32;
+            // The parent must be an object,
+            // which isn't already in the chain.
+            if data::get_element_type(parent_id) != 1 {
+                return Err(child_row.to_err_string(&format!(
+                "Tried to inherit from an element which isn't an object (id: {}).", parent_id)));
+            }
+            if chain.iter().any(|(row, ..)| matches!(row, TableRow::Element(id, _) if *id == parent_id)) {
+                return Err(row.to_err_string(&format!(
+                "The inheritance chain of this object contains a cycle (id: {}).", parent_id)));
+            }
+            // Load the parent's script and config.
+            let parent_row = TableRow::Element(parent_id, 1);
+            let parent_source = data::get_element_script(parent_id);
+            let parent_ast = engine.compile(&parent_source)
+            .map_err(|err| parent_row.to_err_string(&err.to_string()))?;
+            let parent_config = engine.parse_json(data::get_element_config(parent_id), false)
+            .map_err(|err| parent_row.to_err_string(&err.to_string()))?;
+            chain.push((parent_row, parent_source, parent_config,
+            parent_ast.iter_functions().map(|func| String::from(func.name)).collect()));
+            ancestors.push(parent_id);
+        }
+        // Start from the root ancestor.
+        chain.reverse();
+
+        // Resolve the config: the last non-empty
+        // `sprites` list in the chain is used, the
+        // default `properties` are merged, and the
+        // `behaviors` and `libraries` lists are
+        // concatenated.
+        let mut sprites = Dynamic::from_array(rhai::Array::new());
+        let mut props = Map::new();
+        let mut behaviors = rhai::Array::new();
+        let mut imports = rhai::Array::new();
+        for (row, _, level_config, _) in chain.iter() {
+            if let Some(level_behaviors) = level_config.get("behaviors") {
+                behaviors.extend(level_behaviors.read_lock::<rhai::Array>().ok_or_else(|| row.to_err_string(
+                "The 'behaviors' attribute of an object's config should be an array."))?.iter().cloned());
+            }
+            if let Some(level_imports) = level_config.get("libraries") {
+                imports.extend(level_imports.read_lock::<rhai::Array>().ok_or_else(|| row.to_err_string(
+                "The 'libraries' attribute of an element's config should be an array."))?.iter().cloned());
+            }
+            if let Some(level_sprites) = level_config.get("sprites") {
+                if level_sprites.read_lock::<rhai::Array>().is_some_and(|arr| !arr.is_empty()) {
+                    sprites = level_sprites.clone();
+                }
+            }
+            if let Some(level_props) = level_config.get("properties") {
+                props.extend(level_props.read_lock::<Map>().ok_or_else(|| row.to_err_string(
+                "The 'properties' attribute of an object's config should be an object-like attribute."))?.clone());
+            }
+        }
+        config.insert("sprites".into(), sprites);
+        config.insert("properties".into(), props.into());
+        config.insert("behaviors".into(), behaviors.into());
+        config.insert("libraries".into(), imports.into());
+
+        // The name a function defined in a certain
+        // level of the chain will have in the merged
+        // AST. Functions which are overridden by a
+        // later level get an internal name.
+        let resolved_name = |level: usize, name: &str| -> String {
+            if chain[level+1..].iter().any(|(.., names)| names.iter().any(|n| n == name)) {
+                format!("__super{}_{}", level, name)
+            } else { String::from(name) }
+        };
+        // Rename the overridden functions and the
+        // `super_` calls in every level's script,
+        // compile it and merge it into the result.
+        let mut merged = AST::empty();
+        let mut inherited_fns = HashMap::new();
+        for (level, (level_row, level_source, _, level_names)) in chain.iter().enumerate() {
+            // The functions an ancestor defines are
+            // owned by it in the merged AST.
+            if let (TableRow::Element(id, _), true) = (level_row, level+1 < chain.len()) {
+                inherited_fns.extend(level_names.iter().map(|name| (resolved_name(level, name), *id)));
+            }
+            let renamed = rename_identifiers(level_source, |ident, is_definition| {
+                if is_definition {
+                    return level_names.iter().any(|n| n == ident)
+                    .then(|| resolved_name(level, ident)).filter(|name| name != ident);
+                }
+                // A `super_` call refers to the closest
+                // ancestor which defines the function.
+                let name = ident.strip_prefix("super_")?;
+                (0..level).rev().find(|&ancestor| chain[ancestor].3.iter().any(|n| n == name))
+                .map(|ancestor| resolved_name(ancestor, name))
+            });
+            let level_ast = engine.compile(&renamed)
+            .map_err(|err| level_row.to_err_string(&err.to_string()))?;
+            merged = merged.merge(&level_ast);
+        }
+        Ok((merged, ancestors, inherited_fns))
+    }
+}
+
+/// A struct that will be used to store
+/// a library script, which can be imported\
+/// by elements (and other libraries).
+///
+/// Only the functions defined in the
+/// library's script are kept, because\
+/// they are the only thing imported.
+pub struct Library {
+    pub script: AST,
+    pub imports: Vec<u32>,
+    fn_sources: FnOwners,
+}
+
+/// The libraries which were compiled (or
+/// failed to compile), by rowid.
+type LibraryCache = HashMap<u32, Result<Rc<Library>, String>>;
+
+/// This struct resolves the libraries listed
+/// in the optional `libraries` array of an\
+/// element's config.
+///
+/// Rhai is built with `no_module`, so I
+/// can't use rhai's `import` statement.\
+/// Instead, the functions of every imported
+/// library are merged into the AST of the\
+/// element which imports it. Each library is
+/// compiled once and cached by the resolver,\
+/// until it's forgotten (when it's updated).
+#[derive(Default)]
+pub struct LibraryResolver {
+    cache: RefCell<LibraryCache>,
+}
+
+impl LibraryResolver {
+    /// Creates a new resolver with an empty cache.
+    pub fn new() -> Self {
+        Self { cache: RefCell::new(HashMap::new()) }
+    }
+
+    /// Returns the library with the given
+    /// rowid, or an error if it couldn't be\
+    /// loaded. The library will be compiled
+    /// if it isn't in the cache yet.
+    pub fn resolve(&self, engine: &Engine, id: u32) -> Result<Rc<Library>, String> {
+        self.resolve_chain(engine, id, &mut Vec::new())
+    }
+
+    /// Removes the library with the given rowid
+    /// from the cache, alongside every library\
+    /// which imports it, so they will be compiled
+    /// again the next time they are resolved.
+    pub fn forget(&self, id: u32) {
+        self.cache.borrow_mut().retain(|&lib_id, library| lib_id != id &&
+        library.as_ref().is_ok_and(|library| !library.imports.contains(&id)));
+    }
+
+    /// Merges the functions of the libraries
+    /// imported by an element into the given\
+    /// AST, and returns it alongside the rowids
+    /// of every library it uses (imported directly\
+    /// or not), and a table that tells which
+    /// library each imported function came from.
+    ///
+    /// Functions defined in the element's own
+    /// script override the imported ones.
+    pub fn import(&self, engine: &Engine, row: TableRow, config: &Map, ast: AST)
+     -> Result<(AST, Vec<u32>, FnOwners), String> {
+        self.import_chain(engine, row, config, ast, &mut Vec::new())
+    }
+
+    /// Resolves a library, while keeping track of
+    /// the libraries which are being compiled\
+    /// (the chain), to detect import cycles.
+    fn resolve_chain(&self, engine: &Engine, id: u32, chain: &mut Vec<u32>) -> Result<Rc<Library>, String> {
+        if let Some(library) = self.cache.borrow().get(&id) {
+            return library.clone();
+        }
+        if chain.contains(&id) {
+            return Err(format!("The libraries import each other in a cycle (name: '{}', id: {}).",
+            data::get_element_name(id), id));
+        }
+        if data::get_element_type(id) != 4 {
+            return Err(format!("Tried to import an element which isn't a library (name: '{}', id: {}).",
+            data::get_element_name(id), id));
+        }
+        chain.push(id);
+        let library = self.compile(engine, id, chain);
+        chain.pop();
+        self.cache.borrow_mut().insert(id, library.clone());
+        library
+    }
+
+    /// Loads and compiles a library's script,
+    /// and merges the libraries it imports into it.
+    fn compile(&self, engine: &Engine, id: u32, chain: &mut Vec<u32>) -> Result<Rc<Library>, String> {
+        let row = TableRow::Element(id, 4);
+        let script = engine.compile(data::get_element_script(id))
+        .map_err(|err| row.to_err_string(&err.to_string()))?.clone_functions_only();
+        let config = engine.parse_json(data::get_element_config(id), false)
+        .map_err(|err| row.to_err_string(&err.to_string()))?;
+        // The library's own functions are named
+        // after it in errors, unlike the elements'.
+        let own_fns: Vec<(String, u32)> = script.iter_functions()
+        .map(|func| (String::from(func.name), id)).collect();
+        let (script, imports, mut fn_sources) = self.import_chain(engine, row, &config, script, chain)?;
+        fn_sources.extend(own_fns);
+        Ok(Rc::new(Library { script, imports, fn_sources }))
+    }
+
+    /// Imports the libraries listed in the config
+    /// of an element or a library (see `import`).
+    fn import_chain(&self, engine: &Engine, row: TableRow, config: &Map, ast: AST, chain: &mut Vec<u32>)
+     -> Result<(AST, Vec<u32>, FnOwners), String> {
+        let mut imports: Vec<u32> = Vec::new();
+        let mut fn_sources = HashMap::new();
+        let Some(list) = config.get("libraries") else {
+            return Ok((ast, imports, fn_sources));
+        };
+        let list = list.read_lock::<rhai::Array>().ok_or_else(|| row.to_err_string(
+        "The 'libraries' attribute of an element's config should be an array."))?.clone();
+        let mut merged = AST::empty();
+        for id in list.iter() {
+            let id = dynamic_to_number(id).map_err(|_| row.to_err_string(
+            "The 'libraries' attribute of an element's config should be an integer array."))? as u32;
+            let library = self.resolve_chain(engine, id, chain)
+            .map_err(|err| format!("{}\nimported by {}.", err, row.describe()))?;
+            // Later libraries override the
+            // functions of earlier ones.
+            merged = merged.merge(&library.script);
+            fn_sources.extend(library.fn_sources.iter().map(|(name, &id)| (name.clone(), id)));
+            for &import in library.imports.iter().chain([&id]) {
+                if !imports.contains(&import) { imports.push(import); }
+            }
+        }
+        // The element's own functions aren't imported.
+        for func in ast.iter_functions() {
+            fn_sources.remove(func.name);
+        }
+        Ok((merged.merge(&ast), imports, fn_sources))
+    }
+}
+
+/// Collects the rowids of the behaviors
+/// which should be attached to an object,\
+/// listed in the optional `behaviors` array
+/// of the object's config, and then in the\
+/// one of the instance's config (if provided).
+fn behavior_ids(config: &Map, instance: Option<&Map>) -> Vec<u32> {
+    [Some(config), instance].into_iter().flatten()
+    .filter_map(|map| map.get("behaviors"))
+    .flat_map(|list| list.read_lock::<rhai::Array>()
+    .expect("The 'behaviors' attribute of an object or an instance should be an integer array.").clone())
+    .map(|id| dynamic_to_number(&id)
+    .expect("The 'behaviors' attribute of an object or an instance should be an integer array.") as u32)
+    .collect()
+}
+
+/// Collects the definitions of the behaviors
+/// which should be attached to an object\
+/// (see `behavior_ids`), and returns an error
+/// if any of them couldn't be loaded, or isn't\
+/// a behavior.
+fn behavior_defs(config: &Map, instance: Option<&Map>, element_defs: &ElementDefinitions)
+ -> Result<Vec<Rc<ElementDefinition>>, String> {
+    let mut defs = Vec::new();
+    for id in behavior_ids(config, instance) {
+        let Some(def) = element_defs.get(&id) else {
+            return Err(format!("Tried to attach a behavior which doesn't exist (id: {}).", id));
+        };
+        let def = def.as_ref()?;
+        if !matches!(def.row, TableRow::Element(_, 3)) {
+            return Err(format!("Tried to attach {}, which isn't a behavior, as a behavior.", def.row.describe()));
+        }
+        defs.push(Rc::clone(def));
+    }
+    Ok(defs)
+}
+
+/// Scans a script's source, and replaces
+/// identifiers using the provided function,\
+/// which receives the identifier and whether
+/// it's the name in a function definition,\
+/// and returns the replacement, if any.
+///
+/// Comments, strings and characters are
+/// skipped, and line breaks are preserved,\
+/// so positions in error messages will still
+/// point to the right lines.
+fn rename_identifiers(source: &str, rename: impl Fn(&str, bool) -> Option<String>) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let mut result = String::with_capacity(source.len());
+    let mut after_fn = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        match chars[i] {
+            // Line comments
+            '/' if chars.get(i+1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' { i += 1; }
+            },
+            // Block comments (which can be nested)
+            '/' if chars.get(i+1) == Some(&'*') => {
+                let mut depth = 0;
+                while i < chars.len() {
+                    if chars[i] == '/' && chars.get(i+1) == Some(&'*') { depth += 1; i += 2; continue; }
+                    if chars[i] == '*' && chars.get(i+1) == Some(&'/') {
+                        depth -= 1; i += 2;
+                        if depth == 0 { break; }
+                        continue;
+                    }
+                    i += 1;
+                }
+            },
+            // Strings, characters and string templates
+            quote @ ('"' | '\'' | '`') => {
+                i += 1;
+                while i < chars.len() && chars[i] != quote {
+                    if chars[i] == '\\' { i += 1; }
+                    i += 1;
+                }
+                i += 1;
+            },
+            // Identifiers and keywords
+            c if c.is_alphabetic() || c == '_' => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') { i += 1; }
+                let ident: String = chars[start..i].iter().collect();
+                if let Some(replacement) = rename(&ident, after_fn) {
+                    result.push_str(&replacement);
+                } else {
+                    result.push_str(&ident);
+                }
+                after_fn = ident == "fn";
+                continue;
+            },
+            // Numbers (so suffixes won't be read as identifiers)
+            c if c.is_ascii_digit() => {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') { i += 1; }
+            },
+            c => {
+                i += 1;
+                if !c.is_whitespace() { after_fn = false; }
+            },
+        }
+        result.extend(chars[start..i.min(chars.len())].iter());
+    }
+    result
 }
 
 /// A struct that will store the
@@ -112,14 +776,16 @@ impl ElementDefinition {
 /// maintained scope among other things.
 pub struct ElementResources {
     pub definition: Rc<ElementDefinition>,
-    scope: Scope<'static>
+    scope: Scope<'static>,
+    behaviors: Vec<ElementResources>,
+    api_names: Vec<String>,
 }
 
 impl ElementResources {
     /// Creates a new element resources\
     /// struct using a given element definition.
     fn new(definition: Rc<ElementDefinition>) -> Self {
-        Self { definition, scope: Scope::new() }
+        Self { definition, scope: Scope::new(), behaviors: Vec::new(), api_names: Vec::new() }
     }
     /// Recycles an existing element resources\
     /// struct using a given element definition.
@@ -127,6 +793,60 @@ impl ElementResources {
         self.definition = definition;
         self.scope.clear();
     }
+    /// Attaches behaviors to the element,
+    /// using the given behavior definitions.\
+    /// Every behavior gets it's own resources,
+    /// which are recycled if they already exist.
+    fn attach_behaviors(&mut self, definitions: &[Rc<ElementDefinition>]) {
+        for (idx, definition) in definitions.iter().enumerate() {
+            if idx < self.behaviors.len() {
+                self.behaviors[idx].recycle(Rc::clone(definition));
+                continue;
+            }
+            self.behaviors.push(Self::new(Rc::clone(definition)));
+        }
+        self.behaviors.truncate(definitions.len());
+    }
+    /// Rebuilds the behaviors attached to the
+    /// element if they don't match the given\
+    /// behavior definitions (after the element's
+    /// config was changed). Behaviors which stay\
+    /// attached keep their resources, and the
+    /// new ones get the shared `Object` API\
+    /// and run their scripts.
+    fn rebuild_behaviors(&mut self, engine: &Engine, definitions: &[Rc<ElementDefinition>],
+    object: &Rc<RefCell<Dynamic>>) -> Result<(), String> {
+        if self.behaviors.len() == definitions.len() && self.behaviors.iter().zip(definitions)
+        .all(|(behavior, definition)| behavior.definition.row == definition.row) { return Ok(()); }
+        let mut old_behaviors = std::mem::take(&mut self.behaviors);
+        let mut new_indices = Vec::new();
+        for definition in definitions {
+            if let Some(idx) = old_behaviors.iter().position(|behavior| behavior.definition.row == definition.row) {
+                self.behaviors.push(old_behaviors.remove(idx));
+                continue;
+            }
+            let mut behavior = Self::new(Rc::clone(definition));
+            behavior.push_shared("Object", object);
+            new_indices.push(self.behaviors.len());
+            self.behaviors.push(behavior);
+        }
+        for idx in new_indices {
+            if let Err(err) = self.behaviors[idx].run_script(engine) {
+                return Err(format!("{}\n(attached to {})", err, self.definition.row.describe()));
+            }
+        }
+        Ok(())
+    }
+    /// Pushes a shared variable (like an
+    /// element's local API) into the scope\
+    /// of the element and the scopes of
+    /// every behavior attached to it.
+    fn push_shared(&mut self, name: &str, value: &Rc<RefCell<Dynamic>>) {
+        self.scope.push_dynamic(name, Dynamic::from(Rc::clone(value)));
+        for behavior in self.behaviors.iter_mut() {
+            behavior.push_shared(name, value);
+        }
+    }
     /// Runs the element's script
     /// with the current scope and\
     /// return an error if any occured.
@@ -135,33 +855,193 @@ impl ElementResources {
     /// the global scope will be executed\
     /// and every variable defined in it will
     /// stay in the scope until it's cleared.
+    ///
+    /// The scripts of the behaviors attached
+    /// to the element run afterwards in order.
     pub fn run_script(&mut self, engine: &Engine) -> Result<(), String> {
-        if let Some(err) = engine.run_ast_with_scope
-        (&mut self.scope, &self.definition.script).err() {
-            return Err(self.definition.row.to_err_string(&err.to_string()));
+        self.run_body(engine)?;
+        for behavior in self.behaviors.iter_mut() {
+            if let Err(err) = behavior.run_script(engine) {
+                return Err(format!("{}\n(attached to {})", err, self.definition.row.describe()));
+            }
+        }
+        Ok(())
+    }
+    /// Runs the element's own script, without
+    /// the scripts of it's behaviors.
+    ///
+    /// The names of the variables which are
+    /// already in the scope (the local APIs)\
+    /// are kept, so they won't be mistaken for
+    /// the script's variables on a hot reload.
+    fn run_body(&mut self, engine: &Engine) -> Result<(), String> {
+        self.api_names = self.scope.iter_raw().map(|(name, ..)| String::from(name)).collect();
+        let outer_element = RUNNING_ELEMENT.replace(Some((self.definition.row, self.definition.max_operations)));
+        let result = profiler::measure(Some(self.definition.row), "script's body",
+        || engine.run_ast_with_scope(&mut self.scope, &self.definition.script));
+        RUNNING_ELEMENT.set(outer_element);
+        if let Err(err) = result {
+            return Err(self.definition.err_string("script's body", *err));
         }
         Ok(())
     }
+    /// Rebinds the element (and the behaviors
+    /// attached to it) to it's updated definition,\
+    /// if it's rowid is one of the `reloaded` ones
+    /// (the state manager's rowid is 0).
+    ///
+    /// The element's script runs again in a new
+    /// scope, which keeps the local APIs, and\
+    /// every variable the new script defines
+    /// which was already defined by the old one\
+    /// gets back it's old value. After that, the
+    /// optional `on_reload` callback is called.
+    ///
+    /// If the new script's body returns an error,
+    /// it's logged and the element keeps it's\
+    /// old definition and scope. An error in
+    /// `on_reload` is only logged too.
+    pub fn hot_reload(&mut self, engine: &Engine, element_defs: &ElementDefinitions, reloaded: &[u32])
+     -> Result<(), String> {
+        for behavior in self.behaviors.iter_mut() {
+            if let Err(err) = behavior.hot_reload(engine, element_defs, reloaded) {
+                return Err(format!("{}\n(attached to {})", err, self.definition.row.describe()));
+            }
+        }
+        let id = match self.definition.row {
+            TableRow::Metadata => 0,
+            TableRow::Element(id, _) => id,
+            TableRow::Asset(..) => { return Ok(()); },
+        };
+        if !reloaded.contains(&id) { return Ok(()); }
+        // Only a definition which was loaded
+        // successfully can replace the old one.
+        let Some(Ok(definition)) = element_defs.get(&id) else { return Ok(()); };
+        if Rc::ptr_eq(definition, &self.definition) { return Ok(()); }
+        let old_definition = std::mem::replace(&mut self.definition, Rc::clone(definition));
+        // Start a new scope with the local APIs.
+        let old_scope = std::mem::take(&mut self.scope);
+        for (name, is_constant, value) in old_scope.iter_raw()
+        .filter(|(name, ..)| self.api_names.iter().any(|api_name| api_name == name)) {
+            if is_constant {
+                self.scope.push_constant_dynamic(name, value.clone());
+            } else {
+                self.scope.push_dynamic(name, value.clone());
+            }
+        }
+        // If the new script fails, the old one keeps
+        // running with it's scope, like a definition
+        // which failed to compile.
+        if let Err(err) = self.run_body(engine) {
+            self.definition = old_definition;
+            self.scope = old_scope;
+            logger::log(logger::LogLevel::Error, &format!(
+            "{}\nThe previous version will keep running until the error is fixed.", err));
+            return Ok(());
+        }
+        // Restore the values of the variables which
+        // are still defined (constants are taken
+        // from the new script).
+        let names: Vec<String> = self.scope.iter_raw()
+        .filter(|(name, is_constant, _)| !is_constant && !self.api_names.iter().any(|api_name| api_name == name))
+        .map(|(name, ..)| String::from(name)).collect();
+        for name in names {
+            if let (Some(old_value), Some(value)) = (old_scope.get(&name), self.scope.get_mut(&name)) {
+                *value = old_value.clone();
+            }
+        }
+        // An error in `on_reload` is logged like the
+        // ones above, so it won't stop the game.
+        if let Err(err) = self.call_own_fn(engine, "on_reload", ()) {
+            logger::log(logger::LogLevel::Error, &err);
+        }
+        Ok(())
+    }
+    /// Checks if a function with the
+    /// given name is defined in the\
+    /// element's script, or in the script
+    /// of any behavior attached to it.
+    pub fn has_fn(&self, name: &str) -> bool {
+        self.definition.script.iter_functions()
+        .any(|func| { func.name == name}) ||
+        self.behaviors.iter().any(|behavior| behavior.has_fn(name))
+    }
     /// Runs a function defined in the
     /// element's script with the maintained
-    /// scope and return an error if any occured.
-    /// 
+    /// scope and returns the function's\
+    /// result, or an error if any occured.
+    ///
     /// If the function doesn't exist, the call
-    /// will be ignored and no error will be raised.\
+    /// will be ignored, no error will be raised\
+    /// and `None` will be returned, which helps
+    /// telling a missing function apart from\
+    /// a function which returned `()`.
     /// Any new variable defined in the function
     /// will be cleared from the scope after the
     /// function returns.
-    pub fn call_fn(&mut self, engine: &Engine, name: &str, args: impl rhai::FuncArgs) -> Result<(), String> {
-        if !self.definition.script.iter_functions()
-        .any(|func| { func.name == name}) {
-            return Ok(());
+    ///
+    /// The element's `TableRow` is used as the
+    /// call's tag, which lets API functions\
+    /// know which element called them.
+    ///
+    /// After that, the function will be called
+    /// on every behavior attached to the element\
+    /// in order. If the element's script doesn't
+    /// define the function, the result of the\
+    /// first behavior which does is returned.
+    pub fn call_fn(&mut self, engine: &Engine, name: &str, args: impl rhai::FuncArgs + Clone) -> Result<Option<Dynamic>, String> {
+        let mut result = self.call_own_fn(engine, name, args.clone())?;
+        for behavior in self.behaviors.iter_mut() {
+            match behavior.call_fn(engine, name, args.clone()) {
+                Ok(value) => { result = result.or(value); },
+                Err(err) => { return Err(format!("{}\n(attached to {})", err, self.definition.row.describe())); },
+            }
         }
-        if let Some(err) = engine.call_fn_with_options::<()>
-        (rhai::CallFnOptions::new().eval_ast(false), &mut self.scope,
-        &self.definition.script, name, args).err() {
-            return Err(self.definition.row.to_err_string(&err.to_string()));
+        Ok(result)
+    }
+    /// Runs a function defined in the element's
+    /// own script (see `call_fn`), without calling\
+    /// it on the behaviors attached to the element.
+    fn call_own_fn(&mut self, engine: &Engine, name: &str, args: impl rhai::FuncArgs) -> Result<Option<Dynamic>, String> {
+        if !self.definition.script.iter_functions().any(|func| { func.name == name}) {
+            return Ok(None);
+        }
+        // The element is marked as the running one
+        // (so it's operation budget is used) during\
+        // the call, and the caller (if any) is
+        // restored afterwards.
+        let outer_element = RUNNING_ELEMENT.replace(Some((self.definition.row, self.definition.max_operations)));
+        let call_result = profiler::measure(Some(self.definition.row), name,
+        || engine.call_fn_with_options::<Dynamic>
+        (rhai::CallFnOptions::new().eval_ast(false).with_tag(self.definition.row), &mut self.scope,
+        &self.definition.script, name, args));
+        RUNNING_ELEMENT.set(outer_element);
+        match call_result {
+            // The result is flattened, so a shared
+            // value (like an element's local API)\
+            // won't be leaked to the caller.
+            Ok(value) => Ok(Some(value.flatten())),
+            Err(err) => Err(self.definition.err_string(&format!("'{}' callback", name), *err)),
+        }
+    }
+    /// Evaluates a piece of code (like a line typed
+    /// into a console) with the element's scope,\
+    /// and returns it's result, or an error.
+    ///
+    /// The code can call the functions defined in
+    /// the element's script, and the variables it\
+    /// defines stay in the element's scope.
+    pub fn eval(&mut self, engine: &Engine, code: &str) -> Result<Dynamic, String> {
+        let ast = engine.compile_with_scope(&self.scope, code)
+        .map_err(|err| self.definition.row.to_err_string(&err.to_string()))?;
+        let ast = self.definition.script.clone_functions_only().merge(&ast);
+        let outer_element = RUNNING_ELEMENT.replace(Some((self.definition.row, self.definition.max_operations)));
+        let result = engine.eval_ast_with_scope::<Dynamic>(&mut self.scope, &ast);
+        RUNNING_ELEMENT.set(outer_element);
+        match result {
+            Ok(value) => Ok(value.flatten()),
+            Err(err) => Err(self.definition.err_string("console input", *err)),
         }
-        Ok(())
     }
 }
 
@@ -196,8 +1076,11 @@ impl ElementHandler {
     /// used to create the element's
     /// properties, which will be shared
     /// with the element's script scope.
+    ///
+    /// Objects will also get the given
+    /// behaviors attached to them.
     pub fn new(def: &Rc<ElementDefinition>,
-    object_info: Option<element::ObjectInitInfo>) -> Result<Self, String> {
+    object_info: Option<element::ObjectInitInfo>, behaviors: &[Rc<ElementDefinition>]) -> Result<Self, String> {
         // The element handler first gets
         // created only with the element's
         // resources, and with the properties
@@ -272,13 +1155,16 @@ impl ElementHandler {
                     let shared_map = Rc::new(RefCell::new(
                         Dynamic::from(element::Object::new(&element_handler.resources.borrow().definition.config,info))
                     ));
+                    // Attach the behaviors to the object.
+                    element_handler.resources.borrow_mut().attach_behaviors(behaviors);
                     // Share a counted reference
                     // (interior-mutated) to the
                     // "Object" API with the object's
-                    // script scope and with the
-                    // object's element handler.
-                    element_handler.resources.borrow_mut().scope
-                    .push_dynamic("Object", Dynamic::from(Rc::clone(&shared_map)));
+                    // script scope, the scopes of it's
+                    // behaviors and with the object's
+                    // element handler.
+                    element_handler.resources.borrow_mut()
+                    .push_shared("Object", &shared_map);
                     element_handler.properties = shared_map;
                     // Return the element handler.
                     Ok(element_handler)
@@ -312,8 +1198,11 @@ impl ElementHandler {
     /// used to recycle the element's
     /// properties, which will be shared
     /// with the element's script scope.
+    ///
+    /// Objects will also get the given
+    /// behaviors attached to them.
     pub fn recycle(&self, def: &Rc<ElementDefinition>,
-    object_info: Option<element::ObjectInitInfo>) -> Result<(), String> {
+    object_info: Option<element::ObjectInitInfo>, behaviors: &[Rc<ElementDefinition>]) -> Result<(), String> {
         // If the element handler is the
         // state manager, return an error.
         if let TableRow::Metadata = self.resources.borrow().definition.row {
@@ -362,12 +1251,15 @@ impl ElementHandler {
                     self.properties.borrow_mut().write_lock::<element::Object>()
                     .expect("write_lock cast should succeed")
                     .recycle(&self.resources.borrow().definition.config, info);
+                    // Attach the behaviors to the object.
+                    self.resources.borrow_mut().attach_behaviors(behaviors);
                     // Share a counted reference
                     // (interior-mutated) to the
                     // "Object" API with the
-                    // object's script scope
-                    self.resources.borrow_mut().scope
-                    .push_dynamic("Object", Dynamic::from(Rc::clone(&self.properties)));
+                    // object's script scope and
+                    // the scopes of it's behaviors
+                    self.resources.borrow_mut()
+                    .push_shared("Object", &self.properties);
                     
                     Ok(())
                 } else {
@@ -407,6 +1299,8 @@ pub struct GameElementSet {
     pub state_manager: ElementHandler,
     pub cur_scene: ElementHandler,
     pub object_stack: Rc<RefCell<Vec<ElementHandler>>>,
+    pub message_queue: Rc<RefCell<MessageQueue>>,
+    pub default_budget: Rc<Cell<u64>>,
 }
 
 impl GameElementSet {
@@ -433,8 +1327,9 @@ impl GameElementSet {
 
         // Call the function on all the object instances' scripts,
         // while skipping the instances that aren't placed in any
-        // layer in the scene and keeping track of the object stack's
-        // length, which might change during the loop.
+        // layer in the scene or aren't active (except for `init`),
+        // and keeping track of the object stack's length, which
+        // might change during the loop.
         let mut i = 0_usize;
         loop {
             {
@@ -469,6 +1364,19 @@ impl GameElementSet {
                 }
             }// `object_stack_borrow` drops here.
 
+            // Skip the object instance if it isn't active
+            // (if it's properties are in use, it's\
+            // treated as active), unless the function is
+            // `init`, which every instance should run\
+            // once, even if it starts out inactive.
+            let is_active = name == "init" || self.object_stack.borrow().get(i).and_then(|element| element
+            .properties.try_borrow().ok().and_then(|properties| properties.read_lock::<element::Object>()
+            .map(|object| object.active))).unwrap_or(true);
+            if !is_active {
+                i += 1;
+                continue;
+            }
+
             // If the resources were found,
             // use them to call the function
             // on the object instance's script.
@@ -484,6 +1392,280 @@ impl GameElementSet {
         Ok(())
     }
 
+    /// Switches to the scene the state manager's
+    /// `cur_scene` property implies, if it's\
+    /// different from the current scene, and
+    /// calls the `init` function on all the\
+    /// elements after every switch.
+    pub fn follow_cur_scene(&self, engine: &Engine, element_defs: &ElementDefinitions) -> Result<(), String> {
+        // Get the current scene's id.
+        let row_copy = self.cur_scene.resources.borrow().definition.row;
+        if let TableRow::Element(id, 2) = row_copy {
+            // Make a mutable copy of the current scene's id.
+            let mut prv_scene_id = id;
+            // Get the state manager's `cur_scene` property.
+            let mut cur_scene_id = self.state_manager.properties.borrow()
+            .read_lock::<element::Game>()
+            .expect("read_lock cast should succeed").cur_scene;
+            // If the current scene's id is different from
+            // what the state manager's `cur_scene` property
+            // implies, switch to the current scene.
+            while cur_scene_id != prv_scene_id {
+                self.switch_scene(cur_scene_id, engine, element_defs)?;
+                // Call the `init` function on all the elements.
+                self.call_fn_on_all("init", (), engine)?;
+                // Update the previous scene id to
+                // the scene id we just switched to,
+                // and get the `cur_scene` property again.
+                prv_scene_id = cur_scene_id;
+                cur_scene_id = self.state_manager.properties
+                .borrow().read_lock::<element::Game>()
+                .expect("read_lock cast should succeed").cur_scene;
+            // If the state manager switched the scene again in the init function,
+            // keep switching the scene until the two values are equal.
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a single frame of the game's update
+    /// loop, which took `elapsed` milliseconds.\
+    /// It's shared by the main loop and the test
+    /// runner, so a test's frames run exactly\
+    /// like the game's do.
+    ///
+    /// `calibrate_keys` resets the keys which were
+    /// just pressed or released, and `load_elements`\
+    /// loads the elements which were changed, and
+    /// returns their rowids, so they're hot reloaded.
+    pub fn run_frame(&self, engine: &Engine, element_defs: &RefCell<ElementDefinitions>, elapsed: f64,
+    calibrate_keys: impl FnOnce(), load_elements: impl FnOnce(&mut ElementDefinitions) -> Vec<u32>)
+     -> Result<(), String> {
+        // Move the log to the next frame.
+        logger::advance_frame(elapsed);
+        // Call the `update` function on all the elements.
+        self.call_fn_on_all("update", (elapsed as rhai::FLOAT, ), engine)?;
+        // Deliver the messages which were
+        // posted during the update phase.
+        self.deliver_messages(engine);
+        // Calibrate the key states.
+        calibrate_keys();
+        // Switch to the scene the state
+        // manager's `cur_scene` property
+        // implies, if it was changed.
+        self.follow_cur_scene(engine, &element_defs.borrow())?;
+        // Update the cameras' behaviors
+        // before the scene is rendered.
+        self.update_cameras(elapsed);
+        // Load all the elements which
+        // were not already loaded, and
+        // update the ones which were.
+        let reloaded = load_elements(&mut element_defs.borrow_mut());
+        // Rebind the live elements to the
+        // definitions which were updated.
+        self.hot_reload(engine, &element_defs.borrow(), &reloaded)
+    }
+
+    /// Updates the behaviors of the current
+    /// scene's cameras (see `Camera::update`),\
+    /// after the time which elapsed (in
+    /// milliseconds) since the last frame.
+    pub fn update_cameras(&self, elapsed: f64) {
+        let scene_props = self.cur_scene.properties.borrow();
+        let Some(scene) = scene_props.read_lock::<element::Scene>() else { return; };
+        // If the followed object instance doesn't
+        // exist (or isn't alive), the camera only\
+        // updates it's bounds and shake. The object
+        // stack keeps the instances of previous\
+        // scenes after the live ones, so the index
+        // is bound by the live instance count.
+        let follows: Vec<Option<u32>> = scene.cameras.iter().map(|camera| camera.follow.filter(|&idx|
+        (idx as usize) < scene.objects_len+scene.runtimes_len && !scene.runtime_vacants.contains(&idx))).collect();
+        drop(scene);
+        drop(scene_props);
+        let targets = follows.into_iter().map(|follow| follow.and_then(|idx| {
+            let object_stack = self.object_stack.borrow();
+            let properties = object_stack.get(idx as usize)?.properties.borrow();
+            let object = properties.read_lock::<element::Object>()?;
+            Some(object.position.clone())
+        }));
+        let game_props = self.state_manager.properties.borrow();
+        let game = game_props.read_lock::<element::Game>().expect("read_lock cast should succeed");
+        let mut scene_props = self.cur_scene.properties.borrow_mut();
+        let mut scene = scene_props.write_lock::<element::Scene>().expect("write_lock cast should succeed");
+        for (camera, target) in scene.cameras.iter_mut().zip(targets) {
+            camera.update(target, &game, elapsed);
+        }
+    }
+
+    /// Rebinds every live element (the state
+    /// manager, the current scene and the\
+    /// object instances in it) whose definition
+    /// was reloaded, to it's new definition\
+    /// (see `ElementResources::hot_reload`).
+    ///
+    /// Reloaded objects get their behaviors
+    /// rebuilt, in case their config's\
+    /// `behaviors` list was changed, and a
+    /// reloaded state manager's budget is\
+    /// reapplied (see `ScriptBudget::reapply`).
+    pub fn hot_reload(&self, engine: &Engine, element_defs: &ElementDefinitions, reloaded: &[u32])
+     -> Result<(), String> {
+        if reloaded.is_empty() { return Ok(()); }
+        self.state_manager.resources.borrow_mut().hot_reload(engine, element_defs, reloaded)?;
+        // The state manager's config might
+        // have a new `script-budget` attribute.
+        if reloaded.contains(&0) {
+            ScriptBudget::new(&self.state_manager.resources.borrow().definition.config)
+            .reapply(engine, &self.default_budget);
+        }
+        self.cur_scene.resources.borrow_mut().hot_reload(engine, element_defs, reloaded)?;
+        // Take counted references to the resources
+        // of the object instances, so the object stack
+        // won't be borrowed while their scripts run.
+        let (objects_len, instances_len, runtime_vacants) = {
+            let scene_map_borrow = self.cur_scene.properties.borrow();
+            let scene_map_borrow = scene_map_borrow
+            .read_lock::<element::Scene>().expect("read_lock cast should succeed");
+            (scene_map_borrow.objects_len, scene_map_borrow.objects_len+scene_map_borrow.runtimes_len,
+            scene_map_borrow.runtime_vacants.clone())
+        };
+        let instances: Vec<_> = self.object_stack.borrow()
+        .iter().take(instances_len).map(|element| (Rc::clone(&element.resources), Rc::clone(&element.properties)))
+        .collect();
+        let instance_maps = self.cur_scene.resources.borrow().definition
+        .config["object-instances"].clone().into_typed_array::<rhai::Map>().expect(concat!("Every object's",
+        " config should contain a 'object-instances' array, which should only have object-like members."));
+        for (idx, (resources, properties)) in instances.into_iter().enumerate() {
+            if runtime_vacants.contains(&(idx as u32)) { continue; }
+            let mut resources = resources.borrow_mut();
+            resources.hot_reload(engine, element_defs, reloaded)?;
+            // If the object's config was changed, the
+            // list of behaviors it should have might
+            // have changed too (runtime objects don't
+            // have an instance config).
+            let TableRow::Element(id, _) = resources.definition.row else { continue; };
+            if !reloaded.contains(&id) { continue; }
+            let instance_map = if idx < objects_len { instance_maps.get(idx) } else { None };
+            let result = behavior_defs(&resources.definition.config, instance_map, element_defs)
+            .and_then(|behaviors| resources.rebuild_behaviors(engine, &behaviors, &properties));
+            if let Err(err) = result {
+                logger::log(logger::LogLevel::Error, &err);
+            }
+        }
+        Ok(())
+    }
+
+    /// This function delivers the messages
+    /// which were posted using the `post_*`\
+    /// API functions, in the order they were
+    /// posted (FIFO).
+    ///
+    /// Messages which get posted while the
+    /// queue is being delivered will be\
+    /// delivered in the next round. The number
+    /// of rounds is limited by the state\
+    /// manager's `max_message_rounds` property,
+    /// and any message which is left in the\
+    /// queue after the last round will be
+    /// delivered in the next frame.
+    ///
+    /// A message which fails to be delivered
+    /// is logged as an error, and the rest of\
+    /// the queue is still delivered.
+    pub fn deliver_messages(&self, engine: &Engine) {
+        // Get the maximum number of rounds
+        // from the state manager's properties.
+        let max_rounds = self.state_manager.properties.borrow()
+        .read_lock::<element::Game>().expect("read_lock cast should succeed").max_message_rounds;
+        for _ in 0..max_rounds {
+            // Only the messages which are in
+            // the queue when the round starts
+            // will be delivered in this round.
+            let round_len = self.message_queue.borrow().len();
+            if round_len == 0 { break; }
+            for _ in 0..round_len {
+                // Take the message out of the queue
+                // in a seperate statement, so the queue
+                // won't be borrowed while the message is
+                // delivered, and could receive new messages.
+                let message = self.message_queue.borrow_mut().pop_front()
+                .expect("the queue should contain at least as many messages as the round's length");
+                if let Err(err) = self.deliver_message(engine, message) {
+                    logger::log(logger::LogLevel::Error, &err);
+                }
+            }
+        }
+    }
+
+    /// Returns a counted reference to the resources
+    /// of the given target, or `None` if the target\
+    /// is an object instance which doesn't exist in
+    /// the current scene.
+    fn target_resources(&self, target: MessageTarget) -> Option<Rc<RefCell<ElementResources>>> {
+        match target {
+            MessageTarget::StateManager => Some(Rc::clone(&self.state_manager.resources)),
+            MessageTarget::CurScene => Some(Rc::clone(&self.cur_scene.resources)),
+            MessageTarget::Object(idx) => {
+                let stack_len;
+                {
+                    let scene_props_borrow = self.cur_scene.properties.borrow();
+                    let scene_props_borrow = scene_props_borrow
+                    .read_lock::<element::Scene>().expect("read_lock cast should succeed");
+                    stack_len = scene_props_borrow.objects_len+scene_props_borrow.runtimes_len;
+                }// `scene_props_borrow` drops here.
+                self.object_stack.borrow().get(idx as usize).filter(|_| (idx as usize) < stack_len)
+                .map(|element| Rc::clone(&element.resources))
+            },
+        }
+    }
+
+    /// Evaluates a piece of code in the context
+    /// of the given target (see `ElementResources::eval`),\
+    /// and returns a description of the result.
+    pub fn eval(&self, engine: &Engine, target: MessageTarget, code: &str) -> Result<String, String> {
+        let Some(target_res) = self.target_resources(target) else {
+            return Err(String::from("Tried to evaluate code on an object instance which doesn't exist."));
+        };
+        let Ok(mut borrow) = target_res.try_borrow_mut() else {
+            return Err(format!("Tried to evaluate code on {} while it's script was running.",
+            target_res.borrow().definition.row.describe()));
+        };
+        borrow.eval(engine, code).map(|value| debugger::inspect(&value))
+    }
+
+    /// Delivers a single posted message
+    /// to it's target, and returns an error\
+    /// which names both the sender and the
+    /// receiver if the delivery failed.
+    fn deliver_message(&self, engine: &Engine, message: PostedMessage) -> Result<(), String> {
+        // Describe the sender for the error messages.
+        let sender = message.sender.map_or(String::from("an unknown element"), |row| row.describe());
+        // Get a counted reference to
+        // the resources of the target.
+        let Some(target_res) = self.target_resources(message.target) else {
+            // Only an object instance can be missing.
+            let MessageTarget::Object(idx) = message.target else { unreachable!() };
+            return Err(format!(concat!("Tried to deliver the message '{}' posted by {} to",
+            " the object at index {}, which doesn't exist."), message.name, sender, idx));
+        };
+        // Describe the receiver for the error messages.
+        let receiver = target_res.borrow().definition.row.describe();
+        // Try to borrow the target's resources mutably.
+        let Ok(mut borrow) = target_res.try_borrow_mut() else {
+            return Err(format!("Tried to deliver the message '{}' posted by {} to {} while it's script was running.",
+            message.name, sender, receiver));
+        };
+        // call the function with the given name
+        // and arguments, and if an error is raised,
+        // return it while mentioning the sender.
+        if let Err(err) = borrow.call_fn(engine, &format!("message_{}", message.name), message.args) {
+            return Err(format!("{}\nas a result of the message '{}' posted by {} to {}",
+            err, message.name, sender, receiver));
+        }
+        Ok(())
+    }
+
     /// This function is used to
     /// replace the current scene\
     /// with a new one.
@@ -502,13 +1684,17 @@ impl GameElementSet {
     /// game's runtime.
     pub fn switch_scene(&self, scene_id: u32, engine: &Engine,
     element_defs: &ElementDefinitions) -> Result<(), String> {
+        // Messages which are still in the queue and were
+        // posted to the previous scene and it's objects
+        // are discarded (the state manager keeps it's own).
+        self.message_queue.borrow_mut().retain(|message| matches!(message.target, MessageTarget::StateManager));
         // Recycle the current scene's element handler.
         self.cur_scene.recycle(
             element_defs.get(&scene_id).unwrap().as_ref()?,
-            None
+            None, &[]
         )?;
         // Run the current scene's script.
-        self.cur_scene.resources.borrow_mut().run_script(&engine)?;
+        self.cur_scene.resources.borrow_mut().run_script(engine)?;
         
         // Borrow the object stack (mutable)
         let mut object_stack_borrow = self.object_stack.borrow_mut();
@@ -543,33 +1729,55 @@ impl GameElementSet {
                 scene_props_borrow.add_instance(idx as rhai::INT, layer as rhai::INT);
             } // The borrow of the current scene's properties drops here.
 
+            // Get the definitions of the behaviors
+            // which should be attached to the instance.
+            let object_def = element_defs.get(&rowid).unwrap().as_ref()?;
+            let behaviors = behavior_defs(&object_def.config, Some(map), element_defs)?;
             // If the object instance's index can
             // fit in the object stack, recycle
             // the object handler at that index,
             // and then replace it with a new one.
             if idx < object_stack_borrow.len() {
                 object_stack_borrow[idx].recycle(
-                element_defs.get(&rowid).unwrap().as_ref()?,
-                Some(element::ObjectInitInfo::new(idx as u32, map)))?;
+                object_def, Some(element::ObjectInitInfo::new(idx as u32, map)), &behaviors)?;
                 // Run the object instance's script.
-                object_stack_borrow[idx].resources.borrow_mut().run_script(&engine)?;
+                object_stack_borrow[idx].resources.borrow_mut().run_script(engine)?;
             }
             // If the object instance's index
             // can't fit in the object stack,
             // create a new object instance
             // and push it to the object stack.
             object_stack_borrow.push(ElementHandler::new(
-                element_defs.get(&rowid).unwrap().as_ref()?,
-                Some(element::ObjectInitInfo::new(idx as u32, map))
+                object_def,
+                Some(element::ObjectInitInfo::new(idx as u32, map)),
+                &behaviors
             )?);
             // Run the object instance's script.
-            object_stack_borrow.last().unwrap().resources.borrow_mut().run_script(&engine)?;
+            object_stack_borrow.last().unwrap().resources.borrow_mut().run_script(engine)?;
         }
 
         Ok(())
     }
 }
 
+/// Returns the page's client width and
+/// height in real (non-CSS) pixels.
+#[cfg(target_arch = "wasm32")]
+fn page_size() -> (rhai::FLOAT, rhai::FLOAT) {
+    let window = web_sys::window().expect("window cast should succeed");
+    let rect = window.document().expect("document cast should succeed")
+    .document_element().expect("document_element cast should succeed").get_bounding_client_rect();
+    ((rect.width() * window.device_pixel_ratio()).round() as rhai::FLOAT,
+    (rect.height() * window.device_pixel_ratio()).round() as rhai::FLOAT)
+}
+
+/// There's no page when the game runs
+/// headless, so it's size is zero.
+#[cfg(not(target_arch = "wasm32"))]
+fn page_size() -> (rhai::FLOAT, rhai::FLOAT) {
+    (0.0, 0.0)
+}
+
 /// Creates the API for the game engine,
 /// and returns it's integrated components.
 /// 
@@ -631,8 +1839,8 @@ impl GameElementSet {
 /// }
 /// better_example(Object);
 /// ```
-pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
- -> Result<(Rc<Engine>, Rc<GameElementSet>, Rc<RefCell<KeyStates>>), String> {
+pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>, libraries: &LibraryResolver)
+ -> Result<GameApi, String> {
     // Create a rhai engine, into which all
     // the API features will be registered.
     let mut engine = Engine::new_raw();
@@ -643,11 +1851,51 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
     engine.register_type_with_name::<element::ElemPoint>("Point")
           .register_get_set("x", element::ElemPoint::get_x, element::ElemPoint::set_x)
           .register_get_set("y", element::ElemPoint::get_y, element::ElemPoint::set_y)
+          .register_fn("point", element::ElemPoint::new)
+          .register_fn("point", element::ElemPoint::new_int)
+          .register_fn("point", element::ElemPoint::zero)
+          .register_fn("+", element::ElemPoint::add)
+          .register_fn("-", element::ElemPoint::sub)
+          .register_fn("-", element::ElemPoint::neg)
+          .register_fn("*", element::ElemPoint::mul)
+          .register_fn("*", element::ElemPoint::mul_int)
+          .register_fn("*", element::ElemPoint::scalar_mul)
+          .register_fn("*", element::ElemPoint::scalar_mul_int)
+          .register_fn("/", element::ElemPoint::div)
+          .register_fn("/", element::ElemPoint::div_int)
+          .register_fn("==", element::ElemPoint::eq)
+          .register_fn("!=", element::ElemPoint::ne)
+          .register_fn("length", element::ElemPoint::length)
+          .register_get("length", element::ElemPoint::length)
+          .register_fn("normalized", element::ElemPoint::normalized)
+          .register_fn("dot", element::ElemPoint::dot)
+          .register_fn("distance_to", element::ElemPoint::distance_to)
+          .register_fn("lerp", element::ElemPoint::lerp)
+          .register_fn("clamp_length", element::ElemPoint::clamp_length)
+          .register_fn("to_string", element::ElemPoint::describe)
+          .register_fn("to_debug", element::ElemPoint::describe)
           .register_type_with_name::<element::ElemColor>("Color")
           .register_get_set("r", element::ElemColor::get_r, element::ElemColor::set_r)
           .register_get_set("g", element::ElemColor::get_g, element::ElemColor::set_g)
           .register_get_set("b", element::ElemColor::get_b, element::ElemColor::set_b)
           .register_get_set("a", element::ElemColor::get_a, element::ElemColor::set_a)
+          .register_fn("color", element::ElemColor::from_hex)
+          .register_fn("rgba", element::ElemColor::rgba)
+          .register_fn("rgba", element::ElemColor::rgb)
+          .register_fn("rgb", element::ElemColor::rgb)
+          .register_fn("hsv", element::ElemColor::hsv)
+          .register_fn("hsv", element::ElemColor::hsva)
+          .register_fn("hsv", element::ElemColor::hsv_int)
+          .register_fn("hsv", element::ElemColor::hsva_int)
+          .register_fn("to_hex", |color: &mut element::ElemColor| color.to_hex())
+          .register_fn("to_hsv", |color: &mut element::ElemColor| color.to_hsv())
+          .register_fn("lerp", element::ElemColor::lerp)
+          .register_fn("mix", element::ElemColor::lerp)
+          .register_fn("*", element::ElemColor::mul)
+          .register_fn("==", element::ElemColor::eq)
+          .register_fn("!=", element::ElemColor::ne)
+          .register_fn("to_string", |color: &mut element::ElemColor| color.to_hex())
+          .register_fn("to_debug", |color: &mut element::ElemColor| color.to_hex())
           .register_type_with_name::<asset::Sprite>("Sprite")
           .register_get("id", asset::Sprite::get_id_rhai)
           .register_get("cur_animation", asset::Sprite::get_cur_animation)
@@ -670,23 +1918,60 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
           .register_get_set("position", element::Object::get_position, element::Object::set_position)
           .register_get_set("scale", element::Object::get_scale, element::Object::set_scale)
           .register_get_set("color", element::Object::get_color, element::Object::set_color)
+          .register_get_set("rotation", element::Object::get_rotation, element::Object::set_rotation)
+          .register_get_set("z", element::Object::get_z, element::Object::set_z)
+          .register_get_set("visible", element::Object::get_visible, element::Object::set_visible)
+          .register_get_set("active", element::Object::get_active, element::Object::set_active)
           .register_get_set("sprites", element::Object::get_sprites, element::Object::set_sprites)
+          .register_get_set("props", element::Object::get_props, element::Object::set_props)
           .register_get("index_in_stack", element::Object::get_index_in_stack)
           .register_type_with_name::<element::Camera>("Camera")
           .register_get_set("position", element::Camera::get_position, element::Camera::set_position)
           .register_get_set("zoom", element::Camera::get_zoom, element::Camera::set_zoom)
+          .register_get_set("rotation", element::Camera::get_rotation, element::Camera::set_rotation)
+          .register_fn("zoom_at", element::Camera::zoom_at)
           .register_get_set("color", element::Camera::get_color, element::Camera::set_color)
+          .register_get_set("viewport", element::Camera::get_viewport, element::Camera::set_viewport)
+          .register_get_set("layers", element::Camera::get_layers, element::Camera::set_layers)
+          .register_get("following", element::Camera::get_following)
+          .register_get_set("smoothing", element::Camera::get_smoothing, element::Camera::set_smoothing)
+          .register_get_set("dead_zone", element::Camera::get_dead_zone, element::Camera::set_dead_zone)
+          .register_get_set("look_ahead", element::Camera::get_look_ahead, element::Camera::set_look_ahead)
+          .register_get_set("trauma", element::Camera::get_trauma, element::Camera::set_trauma)
+          .register_get_set("shake_offset", element::Camera::get_shake_offset, element::Camera::set_shake_offset)
+          .register_get_set("shake_decay", element::Camera::get_shake_decay, element::Camera::set_shake_decay)
+          .register_fn("follow", element::Camera::follow)
+          .register_fn("stop_following", element::Camera::stop_following)
+          .register_fn("set_bounds", element::Camera::set_bounds)
+          .register_fn("clear_bounds", element::Camera::clear_bounds)
+          .register_fn("add_trauma", element::Camera::add_trauma)
           .register_type_with_name::<element::Layer>("Layer")
-          .register_get("name", element::Layer::get_name)
+          .register_get_set("name", element::Layer::get_name, element::Layer::set_name)
           .register_get("instances", element::Layer::get_instances)
+          .register_get_set("screen_space", element::Layer::get_screen_space, element::Layer::set_screen_space)
+          .register_get_set("parallax", element::Layer::get_parallax, element::Layer::set_parallax)
+          .register_get_set("visible", element::Layer::get_visible, element::Layer::set_visible)
+          .register_get_set("opacity", element::Layer::get_opacity, element::Layer::set_opacity)
+          .register_get_set("tint", element::Layer::get_tint, element::Layer::set_tint)
+          .register_get_set("blend_mode", element::Layer::get_blend_mode, element::Layer::set_blend_mode)
+          .register_get_set("sort_mode", element::Layer::get_sort_mode, element::Layer::set_sort_mode)
           .register_type_with_name::<element::Scene>("Scene")
           .register_get_set("camera", element::Scene::get_camera, element::Scene::set_camera)
+          .register_get_set("cameras", element::Scene::get_cameras, element::Scene::set_cameras)
+          .register_fn("add_camera", element::Scene::add_camera)
+          .register_fn("remove_camera", element::Scene::remove_camera)
           .register_get("objects_len", element::Scene::get_objects_len)
           .register_get("runtimes_len", element::Scene::get_runtimes_len)
           .register_get("runtime_vacants", element::Scene::get_runtime_vacants)
-          .register_get("layers", element::Scene::get_layers)
+          .register_get_set("layers", element::Scene::get_layers, element::Scene::set_layers)
           .register_fn("remove_instance", element::Scene::remove_instance)
           .register_fn("add_instance", element::Scene::add_instance)
+          .register_fn("move_instance", element::Scene::move_instance)
+          .register_fn("layer_index", element::Scene::layer_index)
+          .register_fn("create_layer", element::Scene::create_layer)
+          .register_fn("create_layer", element::Scene::create_top_layer)
+          .register_fn("remove_layer", element::Scene::remove_layer)
+          .register_fn("move_layer", element::Scene::move_layer)
           .register_type_with_name::<element::Game>("Game")
           .register_get_set("canvas_width", element::Game::get_canvas_width, element::Game::set_canvas_width)
           .register_get_set("canvas_height", element::Game::get_canvas_height, element::Game::set_canvas_height)
@@ -694,22 +1979,29 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
           .register_get_set("clear_green", element::Game::get_clear_green, element::Game::set_clear_green)
           .register_get_set("clear_blue", element::Game::get_clear_blue, element::Game::set_clear_blue)
           .register_get_set("fps", element::Game::get_fps, element::Game::set_fps)
+          .register_get_set("max_message_rounds", element::Game::get_max_message_rounds, element::Game::set_max_message_rounds)
           .register_get("cur_scene", element::Game::get_cur_scene)
           .register_set("cur_scene", element::Game::set_cur_scene)
-          .register_get("version", element::Game::get_version);
+          .register_get("version", element::Game::get_version)
+          .register_get_set("seed", element::Game::get_seed, element::Game::set_seed)
+          .register_get("angle_unit", element::Game::get_angle_unit)
+          .register_fn("profile", |_: &mut element::Game| profiler::report());
 
     // Register a variable definition filter.
     // This will prevent scripts from shadowing
     // their own APIs by accident, and will raise
     // an error whenever they do.
+    // (rhai marks this API as deprecated,
+    // because it's considered volatile.)
+    #[allow(deprecated)]
     engine.on_def_var(|is_runtime, info, _| {
-        Ok((info.name != "Scene" && info.name != "Object" && info.name != "Game" && info.name != "State") || !is_runtime)
+        Ok((info.name() != "Scene" && info.name() != "Object" && info.name() != "Game" && info.name() != "State") || !is_runtime)
     });
 
     // Load the state manager's definition,
     // which includes his configuration and script
     element_defs.borrow_mut().insert(0,
-        ElementDefinition::new(&engine,
+        ElementDefinition::new(&engine, libraries,
         TableRow::Metadata
     ));
 
@@ -720,15 +2012,21 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
     // it will be propagated back to the caller.
     let state_manager = ElementHandler::new(
         element_defs.borrow().get(&0).unwrap().as_ref()?,
-        None
+        None, &[]
     )?;
 
+    // Limit the scripts according to the
+    // budget in the state manager's config,
+    // so a runaway callback will be aborted
+    // with an error instead of freezing the page.
+    let default_budget = ScriptBudget::new(&state_manager.resources.borrow().definition.config).apply(&mut engine);
+
     // Receive the rowid of the initial scene from the the state manager.
     let cur_scene_id = state_manager.properties.borrow()
     .read_lock::<element::Game>().expect("read_lock cast should succeed").cur_scene;
     // Load the initial scene's definition.
     element_defs.borrow_mut().insert(cur_scene_id, 
-        ElementDefinition::new(&engine,
+        ElementDefinition::new(&engine, libraries,
         TableRow::Element(cur_scene_id, 2)
     ));
     // Create a new element handler for the current
@@ -736,7 +2034,7 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
     // couldn't be loaded.
     let cur_scene = ElementHandler::new(
         element_defs.borrow().get(&cur_scene_id).unwrap().as_ref()?,
-        None
+        None, &[]
     )?;
     
     // The following lines declare global
@@ -753,10 +2051,28 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
     // Load the standard packages into the rhai engine
     std_package.register_into_engine(&mut engine);
     
+    // Configure the log, using the
+    // state manager's config.
+    logger::configure(&state_manager.resources.borrow().definition.config);
+    // Enable the profiler, if the
+    // state manager's config asks to.
+    profiler::configure(&state_manager.resources.borrow().definition.config);
     // Register a print function to the rhai engine,
     // which will be used by the scripts to print text
-    // to the browser's console.
-    engine.on_print(|text| { log_1(&wasm_bindgen::JsValue::from_str(text)); });
+    // to the browser's console (through the log).
+    engine.on_print(|text| { logger::log(logger::LogLevel::Info, text); });
+    engine.on_debug(|text, _, _| { logger::log(logger::LogLevel::Debug, text); });
+    // Register the logging functions, which tag
+    // the logged message with a level, and let
+    // the scripts read the latest entries.
+    engine.register_fn("log_debug", |message: Dynamic| { logger::log(logger::LogLevel::Debug, &message.to_string()); })
+          .register_fn("log_info", |message: Dynamic| { logger::log(logger::LogLevel::Info, &message.to_string()); })
+          .register_fn("log_warn", |message: Dynamic| { logger::log(logger::LogLevel::Warn, &message.to_string()); })
+          .register_fn("log_error", |message: Dynamic| { logger::log(logger::LogLevel::Error, &message.to_string()); })
+          .register_fn("log_recent", |count: rhai::INT| -> rhai::Array { logger::recent(count.max(0) as usize) });
+    // Register the functions the tests use
+    // to drive the game (see `testing`).
+    testing::register(&mut engine);
     // Create the state table, and share
     // a counted reference (interior-mutated)
     // to it with the state manager's script.
@@ -786,11 +2102,24 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
     // for use in the variable resolver.
     let api_game_props = Rc::clone(&state_manager.properties);
 
+    // Register the script debugger, which
+    // will be able to inspect the same values.
+    debugger::register(&mut engine, Rc::clone(element_defs), Rc::clone(&api_scene_props),
+    Rc::clone(&api_game_props), Rc::clone(&state_table));
+
+    // Create the palette of named colors, which
+    // the variable resolver returns as 'Color'.
+    let api_palette = Dynamic::from_map(element::ElemColor::palette().into_iter()
+    .map(|(name, color)| (name.into(), Dynamic::from(color))).collect());
+
     // Register a variable resolver.
     // This will allow the scripts to
     // read the state table, the 
     // current scene's properties and
     // the state manager's properties ('Game').
+    // (rhai marks this API as deprecated,
+    // because it's considered volatile.)
+    #[allow(deprecated)]
     engine.on_var(move |name, _, context| {
         match name {
             // If the name of the
@@ -834,11 +2163,50 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
                     Ok(Some(api_game_props.borrow().flatten_clone()))
                 }
             },
+            // The palette of named colors
+            // (see `ElemColor::palette`).
+            "Color" => {
+                if context.scope().contains(name) {
+                    // If the script defined it's
+                    // own variable with this name
+                    Ok(None)
+                } else {
+                    Ok(Some(api_palette.clone()))
+                }
+            },
             // Otherwise, continue with the normal variable resolution process.
             _ => Ok(None)
         }
     });
 
+    // Register the random number functions.
+    // The generators are stored in the state
+    // manager's properties, so they are seeded
+    // by `Game.seed`, and every element draws
+    // from the same streams. A stream's name
+    // can be given as the first argument
+    // (or the last one for `rand_shuffle`).
+    let game_props = Rc::clone(&state_manager.properties);
+    engine.register_fn("rand_int", move |min: rhai::INT, max: rhai::INT|
+    random::rand_int(&game_props, None, min, max));
+    let game_props = Rc::clone(&state_manager.properties);
+    engine.register_fn("rand_int", move |stream: &str, min: rhai::INT, max: rhai::INT|
+    random::rand_int(&game_props, Some(stream), min, max));
+    let game_props = Rc::clone(&state_manager.properties);
+    engine.register_fn("rand_float", move || random::rand_float(&game_props, None));
+    let game_props = Rc::clone(&state_manager.properties);
+    engine.register_fn("rand_float", move |stream: &str| random::rand_float(&game_props, Some(stream)));
+    let game_props = Rc::clone(&state_manager.properties);
+    engine.register_fn("rand_choice", move |array: rhai::Array| random::rand_choice(&game_props, None, array));
+    let game_props = Rc::clone(&state_manager.properties);
+    engine.register_fn("rand_choice", move |stream: &str, array: rhai::Array|
+    random::rand_choice(&game_props, Some(stream), array));
+    let game_props = Rc::clone(&state_manager.properties);
+    engine.register_fn("rand_shuffle", move |array: &mut rhai::Array| random::rand_shuffle(&game_props, None, array));
+    let game_props = Rc::clone(&state_manager.properties);
+    engine.register_fn("rand_shuffle", move |array: &mut rhai::Array, stream: &str|
+    random::rand_shuffle(&game_props, Some(stream), array));
+
     // Create the key states table.
     // This table will be used to track
     // the key state of every key on the
@@ -885,6 +2253,57 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
         }
     });
 
+    // Share counted references to the current
+    // scene's properties and the state manager's
+    // properties with the following API functions,
+    // which convert points between the world and
+    // the canvas (in pixels) using the scene's main camera.
+    let cur_scene_props = Rc::clone(&cur_scene.properties);
+    let game_props = Rc::clone(&state_manager.properties);
+    let convert_point = Rc::new(move |point: element::ElemPoint, to_screen: bool| -> element::ElemPoint {
+        let scene_props_borrow = cur_scene_props.borrow();
+        let scene_props_borrow = scene_props_borrow
+        .read_lock::<element::Scene>().expect("read_lock cast should succeed");
+        let game_props_borrow = game_props.borrow();
+        let game_props_borrow = game_props_borrow
+        .read_lock::<element::Game>().expect("read_lock cast should succeed");
+        if to_screen { scene_props_borrow.cameras[0].world_to_screen(&point, &game_props_borrow) }
+        else { scene_props_borrow.cameras[0].screen_to_world(&point, &game_props_borrow) }
+    });
+    let convert = Rc::clone(&convert_point);
+    engine.register_fn("world_to_screen", move |x: rhai::FLOAT, y: rhai::FLOAT|
+    convert(element::ElemPoint::new(x, y), true));
+    let convert = Rc::clone(&convert_point);
+    engine.register_fn("world_to_screen", move |x: rhai::INT, y: rhai::INT|
+    convert(element::ElemPoint::new_int(x, y), true));
+    let convert = Rc::clone(&convert_point);
+    engine.register_fn("world_to_screen", move |point: element::ElemPoint| convert(point, true));
+    let convert = Rc::clone(&convert_point);
+    engine.register_fn("screen_to_world", move |x: rhai::FLOAT, y: rhai::FLOAT|
+    convert(element::ElemPoint::new(x, y), false));
+    let convert = Rc::clone(&convert_point);
+    engine.register_fn("screen_to_world", move |x: rhai::INT, y: rhai::INT|
+    convert(element::ElemPoint::new_int(x, y), false));
+    let convert = Rc::clone(&convert_point);
+    engine.register_fn("screen_to_world", move |point: element::ElemPoint| convert(point, false));
+
+    // Share a counted reference to the state
+    // manager's properties with the following\
+    // API functions, whose angles are in the
+    // unit of `Game.angle_unit` (like the\
+    // `rotation` properties).
+    let game_props = Rc::clone(&state_manager.properties);
+    let angle_unit = Rc::new(move || game_props.borrow()
+    .read_lock::<element::Game>().expect("read_lock cast should succeed").angle_unit);
+    let unit = Rc::clone(&angle_unit);
+    engine.register_fn("angle", move |point: &mut element::ElemPoint| point.angle(unit()));
+    let unit = Rc::clone(&angle_unit);
+    engine.register_fn("rotated", move |point: &mut element::ElemPoint, angle: rhai::FLOAT|
+    point.rotated(angle, unit()));
+    let unit = Rc::clone(&angle_unit);
+    engine.register_fn("rotated", move |point: &mut element::ElemPoint, angle: rhai::INT|
+    point.rotated(angle as rhai::FLOAT, unit()));
+
     // Share a counted reference to the
     // current scene's properties, for
     // use in the following API function.
@@ -919,15 +2338,17 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
     // for use in the following API function.
     let state_manager_res = Rc::clone(&state_manager.resources);
     engine.register_fn("message_state_manager", move |context: rhai::NativeCallContext,
-    name: &str, args: rhai::Array| -> Result<(), Box<EvalAltResult>> {
+    name: &str, args: rhai::Array| -> Result<Dynamic, Box<EvalAltResult>> {
         // if the state manager's resources are not borrowed,
         if let Ok(mut borrow) = state_manager_res.try_borrow_mut() {
             // call the function with the given name and
-            // arguments, and if an error is raised, return it.
-            if let Some(err) = borrow.call_fn(context.engine(),&format!("message_{}", name), args).err() {
+            // arguments, and return it's result. If the
+            // function doesn't exist, return `()`.
+            match borrow.call_fn(context.engine(),&format!("message_{}", name), args) {
+                Ok(result) => Ok(result.unwrap_or(Dynamic::UNIT)),
                 // Mention the use of the messaging API in the error message
-                Err(format!("{}\nas a result of a call to 'message_state_manager'", err).into())
-            } else { Ok(()) }
+                Err(err) => Err(format!("{}\nas a result of a call to 'message_state_manager'", err).into()),
+            }
         } else {
             // otherwise, return an error
             Err(concat!("Can't use the 'message_state_manager' function while the state manager's script is running",
@@ -941,15 +2362,17 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
     // for use in the following API function.
     let cur_scene_res = Rc::clone(&cur_scene.resources);
     engine.register_fn("message_cur_scene", move |context: rhai::NativeCallContext,
-    name: &str, args: rhai::Array| -> Result<(), Box<EvalAltResult>> {
+    name: &str, args: rhai::Array| -> Result<Dynamic, Box<EvalAltResult>> {
         // if the current scene's resources are not borrowed,
         if let Ok(mut borrow) = cur_scene_res.try_borrow_mut() {
             // call the function with the given name and
-            // arguments, and if an error is raised, return it.
-            if let Some(err) = borrow.call_fn(context.engine(),&format!("message_{}", name), args).err() {
+            // arguments, and return it's result. If the
+            // function doesn't exist, return `()`.
+            match borrow.call_fn(context.engine(),&format!("message_{}", name), args) {
+                Ok(result) => Ok(result.unwrap_or(Dynamic::UNIT)),
                 // Mention the use of the messaging API in the error message
-                Err(format!("{}\nas a result of a call to 'message_cur_scene'", err).into())
-            } else { Ok(()) }
+                Err(err) => Err(format!("{}\nas a result of a call to 'message_cur_scene'", err).into()),
+            }
         } else {
             // otherwise, return an error
             Err(concat!("Can't use the 'message_cur_scene' function while the current scene's script is running",
@@ -958,6 +2381,67 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
         }
     });
 
+    // Create the message queue, which will
+    // store the messages posted by the following
+    // API functions until they get delivered
+    // between the update phases of the main loop.
+    // Posting a message, unlike sending it with
+    // one of the messaging functions, can be done
+    // while the target's script is running.
+    let message_queue: Rc<RefCell<MessageQueue>> = Rc::new(RefCell::new(VecDeque::new()));
+
+    let api_message_queue = Rc::clone(&message_queue);
+    engine.register_fn("post_state_manager", move |context: rhai::NativeCallContext,
+    name: &str, args: rhai::Array| {
+        api_message_queue.borrow_mut().push_back(PostedMessage {
+            sender: message_sender(&context),
+            target: MessageTarget::StateManager,
+            name: String::from(name), args,
+        });
+    });
+
+    let api_message_queue = Rc::clone(&message_queue);
+    engine.register_fn("post_scene", move |context: rhai::NativeCallContext,
+    name: &str, args: rhai::Array| {
+        api_message_queue.borrow_mut().push_back(PostedMessage {
+            sender: message_sender(&context),
+            target: MessageTarget::CurScene,
+            name: String::from(name), args,
+        });
+    });
+
+    // The messaging functions return `()` when the
+    // message handler doesn't exist, which is also
+    // what a handler might return. The following
+    // API functions help telling the two apart.
+    let state_manager_res = Rc::clone(&state_manager.resources);
+    engine.register_fn("state_manager_handles_message",
+    move |name: &str| -> Result<bool, Box<EvalAltResult>> {
+        // if the state manager's resources are not
+        // borrowed, check if the handler exists.
+        if let Ok(borrow) = state_manager_res.try_borrow() {
+            Ok(borrow.has_fn(&format!("message_{}", name)))
+        } else {
+            // otherwise, return an error
+            Err(concat!("Can't use the 'state_manager_handles_message' function while the state manager's",
+            " script is running (is handling another callback).").into())
+        }
+    });
+
+    let cur_scene_res = Rc::clone(&cur_scene.resources);
+    engine.register_fn("cur_scene_handles_message",
+    move |name: &str| -> Result<bool, Box<EvalAltResult>> {
+        // if the current scene's resources are not
+        // borrowed, check if the handler exists.
+        if let Ok(borrow) = cur_scene_res.try_borrow() {
+            Ok(borrow.has_fn(&format!("message_{}", name)))
+        } else {
+            // otherwise, return an error
+            Err(concat!("Can't use the 'cur_scene_handles_message' function while the current scene's",
+            " script is running (is handling another callback).").into())
+        }
+    });
+
     // Converts an element's name to it's id.
     // Returns an error if the name doesn't exist.
     engine.register_fn("element_name_to_id", |name: &str| -> Result<rhai::INT, Box<EvalAltResult>> {
@@ -996,20 +2480,8 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
 
     // Uses web-sys to get the client
     // width and height of the whole page.
-    engine.register_fn("get_page_width", || -> rhai::FLOAT {
-        let window = web_sys::window().expect("window cast should succeed");
-        // returns the page's client width in real (non-CSS) pixels
-        (window.document().expect("document cast should succeed")
-        .document_element().expect("document_element cast should succeed")
-        .get_bounding_client_rect().width() * window.device_pixel_ratio()).round() as rhai::FLOAT
-    });
-    engine.register_fn("get_page_height", || -> rhai::FLOAT {
-        let window = web_sys::window().expect("window cast should succeed");
-        // returns the page's client height in real (non-CSS) pixels
-        (window.document().expect("document cast should succeed")
-        .document_element().expect("document_element cast should succeed")
-        .get_bounding_client_rect().height() * window.device_pixel_ratio()).round() as rhai::FLOAT
-    });
+    engine.register_fn("get_page_width", || -> rhai::FLOAT { page_size().0 })
+          .register_fn("get_page_height", || -> rhai::FLOAT { page_size().1 });
 
     // For some weird reason, the rhai standard
     // package doesn't include a 'min' and 'max'
@@ -1019,19 +2491,19 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
     // Because of this, I had to implement them myself.
     engine.register_fn("min",
     |value1: rhai::INT, value2: rhai::INT| -> rhai::INT {
-        return value1.min(value2);
+        value1.min(value2)
     });
     engine.register_fn("max",
     |value1: rhai::INT, value2: rhai::INT| -> rhai::INT {
-        return value1.max(value2);
+        value1.max(value2)
     });
     engine.register_fn("min",
     |value1: rhai::FLOAT, value2: rhai::FLOAT| -> rhai::FLOAT {
-        return value1.min(value2);
+        value1.min(value2)
     });
     engine.register_fn("max",
     |value1: rhai::FLOAT, value2: rhai::FLOAT| -> rhai::FLOAT {
-        return value1.max(value2);
+        value1.max(value2)
     });
     
     // Here the state manager and the current scene's
@@ -1096,15 +2568,29 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
             // isn'y already loaded, load it.
             if !element_defs.borrow().contains_key(&rowid) {
                 element_defs.borrow_mut().insert(rowid,
-                    ElementDefinition::new(&engine,
+                    ElementDefinition::new(&engine, libraries,
                     TableRow::Element(rowid, 1)
                 ));
             }
+            // Load the definitions of the behaviors
+            // which should be attached to the instance,
+            // if they aren't already loaded.
+            let object_def = Rc::clone(element_defs.borrow().get(&rowid).unwrap().as_ref()?);
+            for behavior_id in behavior_ids(&object_def.config, Some(map)) {
+                if !element_defs.borrow().contains_key(&behavior_id) {
+                    element_defs.borrow_mut().insert(behavior_id,
+                        ElementDefinition::new(&engine, libraries,
+                        TableRow::Element(behavior_id, 3)
+                    ));
+                }
+            }
+            let behaviors = behavior_defs(&object_def.config, Some(map), &element_defs.borrow())?;
             // Create a new element handler for the
             // instance and push it to the object stack.
             object_stack_borrow.push(ElementHandler::new(
-                element_defs.borrow().get(&rowid).unwrap().as_ref()?,
-                Some(element::ObjectInitInfo::new(idx, map))
+                &object_def,
+                Some(element::ObjectInitInfo::new(idx, map)),
+                &behaviors
             )?);
             // Run the instance's script.
             object_stack_borrow.last().unwrap().resources.borrow_mut().run_script(&engine)?;
@@ -1125,7 +2611,7 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
         // Check if the index is in the range of the current scene's objects
         if idx >= (scene_props_borrow.objects_len+scene_props_borrow.runtimes_len) as rhai::INT {
             return Err(Box::new(EvalAltResult::ErrorArrayBounds(scene_props_borrow.objects_len+
-            scene_props_borrow.runtimes_len, idx, context.position())));
+            scene_props_borrow.runtimes_len, idx, context.call_position())));
         }
         // Borrow the object stack for reading (immutable)
         let object_stack_borrow;
@@ -1146,14 +2632,14 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
         } else {
             // otherwise, return an error
             Err(Box::new(EvalAltResult::ErrorArrayBounds(scene_props_borrow.objects_len+
-            scene_props_borrow.runtimes_len, idx, context.position())))
+            scene_props_borrow.runtimes_len, idx, context.call_position())))
         }
     });
 
     let api_object_stack = Rc::clone(&object_stack);
     let cur_scene_props = Rc::clone(&cur_scene.properties);
     engine.register_fn("message_object", move |context: rhai::NativeCallContext, idx: rhai::INT, 
-    name: &str, args: rhai::Array| -> Result<(), Box<EvalAltResult>> {
+    name: &str, args: rhai::Array| -> Result<Dynamic, Box<EvalAltResult>> {
         // Because we need to message an object,
         // we can't keep the current scene's properties
         // borrowed for reading, because the message might
@@ -1173,11 +2659,8 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
 
         // If the index is out of
         // bounds, return an error.
-        if idx >= (objects_len+runtimes_len) as rhai::INT {
-            let info = "Argument 'idx' was out of bounds in call to 'message_object'.";
-            let full_s = &format!("{}.\nTried to find index {} on the object stack, when it only had {} elements.",
-            info, idx, objects_len+runtimes_len);
-            return Err(full_s.into());
+        if idx < 0 || idx >= (objects_len+runtimes_len) as rhai::INT {
+            return Err(object_index_error("message_object", idx, objects_len+runtimes_len));
         }
         // What I said about the current scene's properties
         // also applies to the object stack, so we'll need
@@ -1210,11 +2693,13 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
             // Try to borrow the object's resources mutably.
             if let Ok(mut borrow) = element.try_borrow_mut() {
                 // call the function with the given name and
-                // arguments, and if an error is raised, return it.
-                if let Some(err) = borrow.call_fn(context.engine(),&format!("message_{}", name), args).err() {
+                // arguments, and return it's result. If the
+                // function doesn't exist, return `()`.
+                match borrow.call_fn(context.engine(),&format!("message_{}", name), args) {
+                    Ok(result) => Ok(result.unwrap_or(Dynamic::UNIT)),
                     // Mention the use of the messaging API in the error message
-                    Err(format!("{}\nas a result of a call to 'message_object'", err).into())
-                } else { Ok(()) }
+                    Err(err) => Err(format!("{}\nas a result of a call to 'message_object'", err).into()),
+                }
             } else {
                 // otherwise, return an error
                 Err(concat!("Can't use the 'message_object' function while that object's script is running",
@@ -1223,10 +2708,70 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
             }
         } else {
             // otherwise, the object doesn't exist, so return an error
-            let info = "Argument 'idx' was out of bounds in call to 'message_object'.";
-            let full_s = &format!("{}.\nTried to find index {} on the object stack, when it only had {} elements.",
-            info, idx, objects_len+runtimes_len);
-            Err(full_s.into())
+            Err(object_index_error("message_object", idx, objects_len+runtimes_len))
+        }
+    });
+
+    let api_message_queue = Rc::clone(&message_queue);
+    let cur_scene_props = Rc::clone(&cur_scene.properties);
+    engine.register_fn("post_object", move |context: rhai::NativeCallContext, idx: rhai::INT,
+    name: &str, args: rhai::Array| -> Result<(), Box<EvalAltResult>> {
+        // If the index is out of
+        // bounds, return an error.
+        let objects_len: usize;
+        {
+            let scene_props_borrow = cur_scene_props.borrow();
+            let scene_props_borrow = scene_props_borrow
+            .read_lock::<element::Scene>().expect("read_lock cast should succeed");
+            objects_len = scene_props_borrow.objects_len+scene_props_borrow.runtimes_len;
+        }// scene_props_borrow drops here.
+        if idx < 0 || idx >= objects_len as rhai::INT {
+            return Err(object_index_error("post_object", idx, objects_len));
+        }
+        // Add the message to the queue.
+        api_message_queue.borrow_mut().push_back(PostedMessage {
+            sender: message_sender(&context),
+            target: MessageTarget::Object(idx as u32),
+            name: String::from(name), args,
+        });
+        Ok(())
+    });
+
+    let api_object_stack = Rc::clone(&object_stack);
+    let cur_scene_props = Rc::clone(&cur_scene.properties);
+    engine.register_fn("object_handles_message", move |idx: rhai::INT,
+    name: &str| -> Result<bool, Box<EvalAltResult>> {
+        // Get the bounds of the object stack
+        // from the current scene's properties.
+        let objects_len: usize;
+        {
+            let scene_props_borrow = cur_scene_props.borrow();
+            let scene_props_borrow = scene_props_borrow
+            .read_lock::<element::Scene>().expect("read_lock cast should succeed");
+            objects_len = scene_props_borrow.objects_len+scene_props_borrow.runtimes_len;
+        }// scene_props_borrow drops here.
+        // Borrow the object stack for reading (immutable)
+        let object_stack_borrow;
+        if let Ok(borrow) = api_object_stack.try_borrow() {
+            object_stack_borrow = borrow;
+        } else {
+            // The object stack is being borrowed for
+            // writing while the scene is being loaded.
+            return Err("Can't use the global function 'object_handles_message' while the scene is being loaded".into());
+        }
+        // If we find the object in the object stack,
+        if let Some(element) = object_stack_borrow.get(idx as usize).filter(|_| idx >= 0 && (idx as usize) < objects_len) {
+            // check if the handler exists, as long as
+            // the object's resources are not borrowed.
+            if let Ok(borrow) = element.resources.try_borrow() {
+                Ok(borrow.has_fn(&format!("message_{}", name)))
+            } else {
+                Err(concat!("Can't use the 'object_handles_message' function while that object's",
+                " script is running (is handling another callback).").into())
+            }
+        } else {
+            // otherwise, the object doesn't exist, so return an error
+            Err(object_index_error("object_handles_message", idx, objects_len))
         }
     });
 
@@ -1272,12 +2817,31 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
                     return Err(format!("Tried to use 'add_object_to_stack' with a definition of a scene (name: '{}', id: {})",
                     data::get_asset_name(rowid), rowid).into())
                 },
+                TableRow::Element(rowid, 3) => {
+                    return Err(format!("Tried to use 'add_object_to_stack' with a definition of a behavior (name: '{}', id: {})",
+                    data::get_element_name(rowid), rowid).into())
+                },
+                TableRow::Element(rowid, 4) => {
+                    return Err(format!("Tried to use 'add_object_to_stack' with a definition of a library (name: '{}', id: {})",
+                    data::get_element_name(rowid), rowid).into())
+                },
+                TableRow::Element(rowid, 5) => {
+                    return Err(format!("Tried to use 'add_object_to_stack' with a definition of a test (name: '{}', id: {})",
+                    data::get_element_name(rowid), rowid).into())
+                },
                 _ => ()
             }
         } else {
             // If the definition doesn't exist, return an error.
             return Err("Tried to use 'add_object_to_stack' with a definition which doesn't exist.".into());
         }
+        // Get the definitions of the behaviors which
+        // should be attached to the new object instance.
+        let behaviors = match behavior_defs(&def_rc_clone.config, None, &api_element_defs.borrow()) {
+            Ok(behaviors) => behaviors,
+            // Mention the use of this function in the error message
+            Err(err) => return Err(format!("{}\nas a result of a call to 'add_object_to_stack'", err).into())
+        };
         // Borrow the current scene's properties for writing (mutable)
         let mut scene_props_borrow = cur_scene_props.borrow_mut();
         let mut scene_props_borrow = scene_props_borrow
@@ -1294,8 +2858,10 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
                 Some(element::ObjectInitInfo {
                 idx_in_stack: (scene_props_borrow.objects_len+scene_props_borrow.runtimes_len) as u32,
                 init_x, init_y, init_scale_x: 1_f32, init_scale_y: 1_f32,
-                init_color: String::from("#FFFFFF"), init_alpha: 255_u8
-            })) {
+                init_color: String::from("#FFFFFF"), init_alpha: 255_u8, init_rotation: 0_f32, init_z: 0_f32,
+                init_visible: true, init_active: true,
+                init_props: Map::new(),
+            }), &behaviors) {
                 // Mention the use of this function in the error message
                 return Err(format!("{}\nas a result of a call to 'add_object_to_stack'", err).into());
             }
@@ -1317,8 +2883,10 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
                 Some(element::ObjectInitInfo {
                 idx_in_stack: (scene_props_borrow.objects_len+scene_props_borrow.runtimes_len) as u32,
                 init_x, init_y, init_scale_x: 1_f32, init_scale_y: 1_f32,
-                init_color: String::from("#FFFFFF"), init_alpha: 255_u8
-            })) {
+                init_color: String::from("#FFFFFF"), init_alpha: 255_u8, init_rotation: 0_f32, init_z: 0_f32,
+                init_visible: true, init_active: true,
+                init_props: Map::new(),
+            }), &behaviors) {
                 // Mention the use of this function in the error message
                 return Err(format!("{}\nas a result of a call to 'add_object_to_stack'", err).into());
             }
@@ -1345,8 +2913,10 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
                 &def_rc_clone, Some(element::ObjectInitInfo {
                     idx_in_stack: (scene_props_borrow.objects_len+scene_props_borrow.runtimes_len) as u32,
                     init_x, init_y, init_scale_x: 1_f32, init_scale_y: 1_f32,
-                    init_color: String::from("#FFFFFF"), init_alpha: 255_u8
-                }));
+                    init_color: String::from("#FFFFFF"), init_alpha: 255_u8, init_rotation: 0_f32, init_z: 0_f32,
+                    init_visible: true, init_active: true,
+                    init_props: Map::new(),
+                }), &behaviors);
                 if element.is_err() {
                     // Mention the use of this function in the error message
                     return Err(format!("{}\nas a result of a call to 'add_object_to_stack'", element.err().unwrap()).into());
@@ -1375,5 +2945,252 @@ pub fn create_api(element_defs: &Rc<RefCell<ElementDefinitions>>)
     // which are related to the API
     // and will need to be maintained
     // throughout the game's operation.
-    Ok((Rc::new(engine), Rc::new(GameElementSet {state_manager, cur_scene, object_stack}), key_states))
-}
\ No newline at end of file
+    Ok((Rc::new(engine), Rc::new(GameElementSet {state_manager, cur_scene, object_stack, message_queue, default_budget}), key_states))
+}
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::game::{HeadlessGame, TEST_SCENE_CONFIG};
+
+    /// The config of an object without assets.
+    const OBJECT_CONFIG: &str = r#"{"sprites": [], "audios": [], "fonts": []}"#;
+
+    /// Returns the config of a scene with a
+    /// single instance of the given object,\
+    /// and the instance's extra attributes.
+    fn one_instance_scene(id: u32, extra: &str) -> String {
+        format!(concat!(r##"{{"object-instances": [{{"id": {}, "layer": 0, "x": 0, "y": 0, "scale-x": 1, "##,
+        r##""scale-y": 1, "color": "#FFFFFF", "alpha": 255{}}}], "layers": ["layer 1"], "##,
+        r##""camera": {{"x": 0, "y": 0, "zoom": 1, "color": "#FFFFFF", "alpha": 255}}}}"##), id, extra)
+    }
+
+    #[test]
+    fn renames_identifiers() {
+        let source = "fn update(elapsed) { super_update(elapsed); update(1); }";
+        let renamed = rename_identifiers(source, |ident, is_definition| match (ident, is_definition) {
+            ("update", true) => Some(String::from("__super0_update")),
+            ("super_update", false) => Some(String::from("__super0_update")),
+            _ => None,
+        });
+        assert_eq!(renamed, "fn __super0_update(elapsed) { __super0_update(elapsed); update(1); }");
+    }
+
+    #[test]
+    fn skips_comments_strings_and_numbers() {
+        let source = concat!("let a = \"a \\\" a\"; // a\n",
+        "/* a /* nested a */ a */ let b = 'a' + `a`;\n",
+        "let c = 1_a + a;");
+        let renamed = rename_identifiers(source, |ident, _| (ident == "a").then(|| String::from("renamed")));
+        assert_eq!(renamed, concat!("let renamed = \"a \\\" a\"; // a\n",
+        "/* a /* nested a */ a */ let b = 'a' + `a`;\n",
+        "let c = 1_a + renamed;"));
+        assert_eq!(renamed.lines().count(), source.lines().count());
+    }
+
+    #[test]
+    fn only_names_after_fn_are_definitions() {
+        let renamed = rename_identifiers("fn  init() { fn_init(); init(); }",
+        |ident, is_definition| is_definition.then(|| format!("<{}>", ident)));
+        assert_eq!(renamed, "fn  <init>() { fn_init(); init(); }");
+    }
+
+    #[test]
+    fn on_reload_errors_are_logged() {
+        let game = HeadlessGame::for_test("State[\"version\"] = 1;", &[("scene", 2, TEST_SCENE_CONFIG, "")]);
+        data::update_test_element(0, None, "State[\"version\"] = 2;\nfn on_reload() { throw \"reload failed\"; }");
+        game.step().expect("An error in on_reload shouldn't stop the game.");
+        assert_eq!(game.eval("State[\"version\"]"), Ok(String::from("2")));
+        let log = logger::recent(1);
+        assert!(log[0].clone().cast::<Map>()["message"].to_string().contains("reload failed"));
+    }
+
+    #[test]
+    fn reloaded_budgets_are_reapplied() {
+        let game = HeadlessGame::for_test("", &[("scene", 2, TEST_SCENE_CONFIG, "")]);
+        assert_eq!(game.eval("let i = 0; while i < 2000 { i += 1; } i"), Ok(String::from("2000")));
+        let config = crate::game::TEST_CONFIG.replacen('{', r#"{"script-budget": {"max-operations": 1000},"#, 1);
+        data::update_test_element(0, Some(&config), "");
+        game.step().unwrap();
+        assert!(game.eval("let i = 0; while i < 2000 { i += 1; } i").is_err_and(|err| err.contains("1000")));
+    }
+
+    #[test]
+    fn object_index_errors_end_with_one_period() {
+        let game = HeadlessGame::for_test("", &[("scene", 2, TEST_SCENE_CONFIG, "")]);
+        for code in ["message_object(3, \"hit\", [])", "post_object(-1, \"hit\", [])"] {
+            let err = game.eval(code).unwrap_err();
+            assert!(err.contains("was out of bounds in call to"), "{}", err);
+            assert!(err.contains("'.\nTried to find index") && !err.contains("..\n"), "{}", err);
+        }
+    }
+
+    /// A state manager which notes every
+    /// message it gets, and answers the\
+    /// first ones by posting another message.
+    const MESSAGE_SCRIPT: &str = concat!("State[\"log\"] = [];\n",
+    "fn message_note(n) { State[\"log\"].push(n); if n < 10 { post_state_manager(\"note\", [n + 10]); } }\n",
+    "fn message_fail() { throw \"delivery failed\"; }");
+
+    #[test]
+    fn messages_are_delivered_in_order_within_the_round_limit() {
+        let game = HeadlessGame::for_test(MESSAGE_SCRIPT, &[("scene", 2, TEST_SCENE_CONFIG, "")]);
+        game.eval("Game.max_message_rounds = 1; post_state_manager(\"note\", [1]); post_state_manager(\"note\", [2]);")
+        .unwrap();
+        game.step().unwrap();
+        // The answers were posted during the
+        // only round, so they wait for the next frame.
+        assert_eq!(game.eval("State[\"log\"]"), Ok(String::from("[1, 2]")));
+        game.step().unwrap();
+        assert_eq!(game.eval("State[\"log\"]"), Ok(String::from("[1, 2, 11, 12]")));
+    }
+
+    #[test]
+    fn failed_deliveries_are_logged() {
+        let game = HeadlessGame::for_test(MESSAGE_SCRIPT, &[("scene", 2, TEST_SCENE_CONFIG, "")]);
+        game.eval("post_state_manager(\"fail\", []); post_state_manager(\"note\", [10]);").unwrap();
+        game.step().expect("A failed delivery shouldn't stop the game.");
+        assert_eq!(game.eval("State[\"log\"]"), Ok(String::from("[10]")));
+        let log = logger::recent(1);
+        assert!(log[0].clone().cast::<Map>()["message"].to_string().contains("delivery failed"));
+    }
+
+    #[test]
+    fn message_object_returns_the_handlers_value() {
+        let game = HeadlessGame::for_test("", &[
+            ("scene", 2, &one_instance_scene(2, ""), ""),
+            ("object", 1, OBJECT_CONFIG, "fn message_double(n) { n * 2 }"),
+        ]);
+        assert_eq!(game.eval("message_object(0, \"double\", [21])"), Ok(String::from("42")));
+        // A message without a handler returns `()`.
+        assert_eq!(game.eval("message_object(0, \"missing\", [])"), Ok(String::from("()")));
+        assert_eq!(game.eval("object_handles_message(0, \"double\")"), Ok(String::from("true")));
+    }
+
+    #[test]
+    fn instance_properties_override_the_defaults() {
+        let game = HeadlessGame::for_test("", &[
+            ("scene", 2, &one_instance_scene(2, r#", "properties": {"hp": 5}"#), ""),
+            ("object", 1, r#"{"sprites": [], "properties": {"hp": 10, "speed": 2}}"#, ""),
+        ]);
+        assert_eq!(game.eval("let props = get_object(0).props; [props.hp, props.speed]"),
+        Ok(String::from("[5, 2]")));
+    }
+
+    #[test]
+    fn objects_inherit_their_parents_config_and_script() {
+        data::use_test_project(crate::game::TEST_CONFIG, "", &[
+            ("parent", 1, r#"{"sprites": [3], "properties": {"hp": 10, "speed": 1}}"#,
+            "fn update(elapsed) { }\nfn hit() { }"),
+            ("child", 1, r#"{"parent": 1, "sprites": [], "properties": {"speed": 2}}"#,
+            "fn update(elapsed) { super_update(elapsed); }"),
+        ]);
+        let definition = ElementDefinition::new(&Engine::new(), &LibraryResolver::default(),
+        TableRow::Element(2, 1)).unwrap();
+        // The parent's sprites are used, because
+        // the child's list is empty.
+        assert_eq!(definition.config["sprites"].to_string(), "[3]");
+        let props = definition.config["properties"].read_lock::<Map>().unwrap().clone();
+        assert_eq!((props["hp"].as_int(), props["speed"].as_int()), (Ok(10), Ok(2)));
+        assert_eq!(definition.ancestors, [1]);
+        // The overridden function keeps an internal
+        // name, and both are owned by the parent.
+        let mut names: Vec<String> = definition.script.iter_functions().map(|func| String::from(func.name)).collect();
+        names.sort();
+        assert_eq!(names, ["__super0_update", "hit", "update"]);
+        assert_eq!((definition.fn_owner("__super0_update"), definition.fn_owner("hit")), (1, 1));
+        assert_eq!(definition.fn_owner("update"), 2);
+    }
+
+    #[test]
+    fn inheritance_cycles_are_errors() {
+        data::use_test_project(crate::game::TEST_CONFIG, "", &[
+            ("first", 1, r#"{"parent": 2, "sprites": []}"#, ""),
+            ("second", 1, r#"{"parent": 1, "sprites": []}"#, ""),
+        ]);
+        let result = ElementDefinition::new(&Engine::new(), &LibraryResolver::default(), TableRow::Element(1, 1));
+        assert!(result.is_err_and(|err| err.contains("cycle")));
+    }
+
+    #[test]
+    fn behaviors_share_the_objects_api() {
+        let game = HeadlessGame::for_test("", &[
+            ("scene", 2, &one_instance_scene(2, r#", "behaviors": [4]"#), ""),
+            ("object", 1, r#"{"sprites": [], "properties": {"ticks": 0}, "behaviors": [3]}"#, ""),
+            ("counter", 3, "{}", "fn update(elapsed) { Object.props.ticks += 1; }"),
+            ("doubler", 3, "{}", "fn update(elapsed) { Object.props.ticks *= 2; }"),
+        ]);
+        // The object's behaviors run before
+        // the ones the instance adds.
+        game.step().unwrap();
+        game.step().unwrap();
+        assert_eq!(game.eval("get_object(0).props.ticks"), Ok(String::from("6")));
+    }
+
+    #[test]
+    fn only_behaviors_can_be_attached() {
+        let game = HeadlessGame::for_test("", &[
+            ("scene", 2, TEST_SCENE_CONFIG, ""),
+            ("object", 1, r#"{"sprites": [], "behaviors": [2]}"#, ""),
+        ]);
+        let element_defs = game.element_defs.borrow();
+        let Some(Ok(definition)) = element_defs.get(&2) else { panic!("The object should be defined.") };
+        assert!(behavior_defs(&definition.config, None, &element_defs).is_err_and(|err| err.contains("isn't a behavior")));
+    }
+
+    #[test]
+    fn libraries_are_imported_with_their_imports() {
+        let game = HeadlessGame::for_test("", &[
+            ("scene", 2, TEST_SCENE_CONFIG, ""),
+            ("math", 4, "{}", "fn double(n) { n * 2 }"),
+            ("more_math", 4, r#"{"libraries": [2]}"#, "fn quadruple(n) { double(double(n)) }"),
+            ("object", 1, r#"{"sprites": [], "libraries": [3]}"#, "fn double(n) { n + n }"),
+        ]);
+        let element_defs = game.element_defs.borrow();
+        let Some(Ok(definition)) = element_defs.get(&4) else { panic!("The object should be defined.") };
+        assert_eq!(definition.libraries, [2, 3]);
+        // The object's own function isn't imported.
+        assert_eq!((definition.fn_owner("quadruple"), definition.fn_owner("double")), (3, 4));
+    }
+
+    #[test]
+    fn library_cycles_are_errors() {
+        data::use_test_project(crate::game::TEST_CONFIG, "", &[
+            ("first", 4, r#"{"libraries": [2]}"#, ""),
+            ("second", 4, r#"{"libraries": [1]}"#, ""),
+            ("object", 1, r#"{"sprites": []}"#, ""),
+        ]);
+        let libraries = LibraryResolver::default();
+        assert!(libraries.resolve(&Engine::new(), 1).is_err_and(|err| err.contains("cycle")));
+        assert!(libraries.resolve(&Engine::new(), 3).is_err_and(|err| err.contains("isn't a library")));
+    }
+
+    #[test]
+    fn hot_reload_keeps_the_instances_state() {
+        let game = HeadlessGame::for_test("", &[
+            ("scene", 2, &one_instance_scene(2, ""), ""),
+            ("object", 1, r#"{"sprites": [], "properties": {"hp": 10}}"#, concat!("fn message_version() { 1 }\n", "fn message_hurt() { Object.props.hp = 3; }")),
+        ]);
+        game.eval("message_object(0, \"hurt\", [])").unwrap();
+        data::update_test_element(2, None, "fn message_version() { 2 }");
+        game.step().unwrap();
+        assert_eq!(game.eval("message_object(0, \"version\", [])"), Ok(String::from("2")));
+        assert_eq!(game.eval("get_object(0).props.hp"), Ok(String::from("3")));
+    }
+
+    #[test]
+    fn inactive_objects_are_only_initialized() {
+        let game = HeadlessGame::for_test("", &[
+            ("scene", 2, &one_instance_scene(2, r#", "active": false, "visible": false"#), ""),
+            ("object", 1, r#"{"sprites": [], "properties": {"inits": 0, "updates": 0}}"#, concat!(
+            "fn init() { Object.props.inits += 1; }\n",
+            "fn update(elapsed) { Object.props.updates += 1; }\n",
+            "fn message_wake() { Object.active = true; }")),
+        ]);
+        game.step().unwrap();
+        let counts = "let object = get_object(0); [object.props.inits, object.props.updates, object.visible]";
+        assert_eq!(game.eval(counts), Ok(String::from("[1, 0, false]")));
+        game.eval("message_object(0, \"wake\", [])").unwrap();
+        game.step().unwrap();
+        assert_eq!(game.eval(counts), Ok(String::from("[1, 1, false]")));
+    }
+}
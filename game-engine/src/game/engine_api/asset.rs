@@ -24,7 +24,7 @@ pub trait Asset {
 /// store a sprite's propertys
 /// for a specific\
 /// `Object`.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Sprite {
     pub id: u32,
     pub cur_animation: String,
@@ -35,12 +35,12 @@ pub struct Sprite {
 }
 
 impl Sprite {
-    pub fn get_id_rhai(&mut self) -> rhai::INT { self.id.clone() as rhai::INT }
+    pub fn get_id_rhai(&mut self) -> rhai::INT { self.id as rhai::INT }
     pub fn get_cur_animation(&mut self) -> String { self.cur_animation.clone() }
-    pub fn get_cur_frame(&mut self) -> rhai::INT { self.cur_frame.clone() as rhai::INT }
-    pub fn get_is_animation_finished(&mut self) -> bool { self.is_animation_finished.clone() }
-    pub fn get_animation_time(&mut self) -> rhai::FLOAT { self.animation_time.clone() as rhai::FLOAT }
-    pub fn get_repeat(&mut self) -> bool { self.repeat.clone() }
+    pub fn get_cur_frame(&mut self) -> rhai::INT { self.cur_frame as rhai::INT }
+    pub fn get_is_animation_finished(&mut self) -> bool { self.is_animation_finished }
+    pub fn get_animation_time(&mut self) -> rhai::FLOAT { self.animation_time as rhai::FLOAT }
+    pub fn get_repeat(&mut self) -> bool { self.repeat }
 
     /// Given an animation name,
     /// this method will setup the\
@@ -149,13 +149,13 @@ pub struct Audio {
 
 #[allow(dead_code)]
 impl Audio {
-    pub fn get_id_rhai(&mut self) -> rhai::INT { self.id.clone() as rhai::INT }
+    pub fn get_id_rhai(&mut self) -> rhai::INT { self.id as rhai::INT }
     pub fn get_tag(&mut self) -> String { self.tag.clone() }
-    pub fn get_audio_time(&mut self) -> rhai::FLOAT { self.audio_time.clone() as rhai::FLOAT }
-    pub fn get_paused(&mut self) -> bool { self.paused.clone() }
-    pub fn get_repeat(&mut self) -> bool { self.repeat.clone() }
-    pub fn get_repeat_start_time(&mut self) -> rhai::FLOAT { self.repeat_start_time.clone() as rhai::FLOAT }
-    pub fn get_volume(&mut self) -> rhai::FLOAT { self.volume.clone() as rhai::FLOAT }
+    pub fn get_audio_time(&mut self) -> rhai::FLOAT { self.audio_time as rhai::FLOAT }
+    pub fn get_paused(&mut self) -> bool { self.paused }
+    pub fn get_repeat(&mut self) -> bool { self.repeat }
+    pub fn get_repeat_start_time(&mut self) -> rhai::FLOAT { self.repeat_start_time as rhai::FLOAT }
+    pub fn get_volume(&mut self) -> rhai::FLOAT { self.volume as rhai::FLOAT }
 
     pub fn play(&mut self) {
         self.audio_time = 0.0;
@@ -169,7 +169,7 @@ impl Audio {
         self.own_tag = false;
     }
 
-    pub fn set_volume(&mut self, value: rhai::FLOAT) { self.volume = value as f32; }
+    pub fn set_volume(&mut self, value: rhai::FLOAT) { self.volume = value; }
     pub fn set_repeat_start_time(&mut self, value: rhai::FLOAT) { self.repeat_start_time = value as f64; }
     pub fn set_repeat(&mut self, value: bool) { self.repeat = value; }
     pub fn set_paused(&mut self, value: bool) { self.paused = value; }
@@ -213,7 +213,7 @@ pub struct Font {
 
 #[allow(dead_code)]
 impl Font {
-    pub fn get_id_rhai(&mut self) -> rhai::INT { self.id.clone() as rhai::INT }
+    pub fn get_id_rhai(&mut self) -> rhai::INT { self.id as rhai::INT }
     pub fn get_text(&mut self) -> String { self.text.clone() }
     pub fn set_text(&mut self, value: &str) { self.text.clear(); self.text.push_str(value); }
 }
@@ -236,7 +236,7 @@ impl Asset for Font {
 /// to use one asset form the list at a time,\
 /// and will be able to switch between them\
 /// using the `cur_asset` attribute.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct AssetList<T: Clone + Asset> {
     pub members: Vec<T>,
     pub cur_asset: usize,
@@ -255,7 +255,7 @@ impl<T: Clone + Asset> AssetList<T> {
     }
 
     pub fn len(&mut self) -> rhai::INT { self.len as rhai::INT }
-    pub fn get_cur_asset(&mut self) -> rhai::INT { self.cur_asset.clone() as rhai::INT }
+    pub fn get_cur_asset(&mut self) -> rhai::INT { self.cur_asset as rhai::INT }
 
     /// This setter will prevent you
     /// from setting the current asset\
@@ -338,11 +338,11 @@ impl<T: Clone + Asset> AssetList<T> {
 
     /// Recyclea an existing `AssetList`
     /// instance using a vector of rowids.
-    pub fn recycle(&mut self, vec: &Vec<Dynamic>) {
+    pub fn recycle(&mut self, vec: &[Dynamic]) {
         self.cur_asset = 0;
         self.len = vec.len();
 
-        for (index, id) in vec.into_iter().enumerate() {
+        for (index, id) in vec.iter().enumerate() {
             let id = dynamic_to_number(id)
             .expect(concat!("Every object's config should",
             " contain a 'sprites' array, which should only have",
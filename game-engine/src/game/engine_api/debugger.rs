@@ -0,0 +1,288 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::prelude::*;
+use rhai::{Engine, Map, Dynamic, debugger::{DebuggerCommand, DebuggerEvent}};
+
+use crate::game::TableRow;
+
+use super::{element, asset, ElementDefinitions, RUNNING_ELEMENT};
+
+#[wasm_bindgen]
+extern "C" {
+    /// Called when the debugger breaks, with a
+    /// JSON report of the paused script (see\
+    /// `report`). The host has to return the
+    /// command which resumes the script:\
+    /// "continue", "step-into", "step-over",
+    /// "step-out" or "detach".
+    ///
+    /// The call is synchronous, because the
+    /// script can't be suspended, so the host\
+    /// has to block until the user picks a
+    /// command (see `load-game.js`, and the\
+    /// editor's debugger panel in `editor.js`).
+    #[wasm_bindgen(js_name=debuggerBreak)]
+    fn debugger_break(report: &str) -> String;
+}
+
+/// The way the debugger should
+/// step through the paused script.
+#[derive(Clone, Copy)]
+enum StepMode {
+    /// Break on the next line.
+    Into,
+    /// Break on the next line of the\
+    /// current function (or a caller).
+    Over,
+    /// Break once the current function returns.
+    Out,
+}
+
+/// A struct that stores the state of
+/// the debugger between callbacks, and\
+/// between calls to the exported functions.
+#[derive(Default)]
+struct DebuggerState {
+    attached: bool,
+    pause_requested: bool,
+    /// Breakpoints by element rowid (0 for
+    /// the state manager) and line.
+    breakpoints: Vec<(u32, usize)>,
+    /// The mode the debugger is stepping
+    /// with, and the call depth it's\
+    /// stepping from.
+    stepping: Option<(StepMode, usize)>,
+    /// The location the debugger last broke
+    /// on, which is forgotten once the script\
+    /// moves to another line, so a line with
+    /// many expressions will only break once.
+    last_break: Option<(u32, usize)>,
+}
+
+thread_local! {
+    static DEBUGGER: RefCell<DebuggerState> = RefCell::new(DebuggerState::default());
+}
+
+/// Attaches the debugger, which will
+/// break on breakpoints from now on.
+#[wasm_bindgen(js_name=debuggerAttach)]
+pub fn debugger_attach() {
+    DEBUGGER.with_borrow_mut(|state| state.attached = true);
+}
+
+/// Detaches the debugger, and lets
+/// every paused script keep running.
+#[wasm_bindgen(js_name=debuggerDetach)]
+pub fn debugger_detach() {
+    DEBUGGER.with_borrow_mut(|state| {
+        state.attached = false;
+        state.pause_requested = false;
+        state.stepping = None;
+    });
+}
+
+/// Adds a breakpoint on a line of an element's
+/// script (the state manager's rowid is 0).
+#[wasm_bindgen(js_name=debuggerSetBreakpoint)]
+pub fn debugger_set_breakpoint(element_id: u32, line: u32) {
+    DEBUGGER.with_borrow_mut(|state| {
+        if !state.breakpoints.contains(&(element_id, line as usize)) {
+            state.breakpoints.push((element_id, line as usize));
+        }
+    });
+}
+
+/// Removes a breakpoint from
+/// a line of an element's script.
+#[wasm_bindgen(js_name=debuggerRemoveBreakpoint)]
+pub fn debugger_remove_breakpoint(element_id: u32, line: u32) {
+    DEBUGGER.with_borrow_mut(|state| state.breakpoints
+    .retain(|&breakpoint| breakpoint != (element_id, line as usize)));
+}
+
+/// Removes every breakpoint.
+#[wasm_bindgen(js_name=debuggerClearBreakpoints)]
+pub fn debugger_clear_breakpoints() {
+    DEBUGGER.with_borrow_mut(|state| state.breakpoints.clear());
+}
+
+/// Makes the debugger break on the next
+/// line of any script which runs.
+#[wasm_bindgen(js_name=debuggerPause)]
+pub fn debugger_pause() {
+    DEBUGGER.with_borrow_mut(|state| state.pause_requested = true);
+}
+
+/// Registers the debugger on the rhai engine.
+///
+/// Rhai's own breakpoints are positions in
+/// a script's source, but every element's\
+/// AST might include functions from other
+/// scripts (ancestors and libraries), so\
+/// the debugger steps through every node
+/// while it's attached, and decides by itself\
+/// when to break, using the element which
+/// owns the running function (rhai keeps the\
+/// caller's source inside merged functions,
+/// so it's looked up in the definition of the\
+/// element which owns the running callback).
+///
+/// The properties of the current scene, the
+/// state manager and the state table are\
+/// included in every report, because most
+/// elements can't reach them through their scope.
+#[allow(deprecated)]
+pub fn register(engine: &mut Engine, element_defs: Rc<RefCell<ElementDefinitions>>, scene_props: Rc<RefCell<Dynamic>>,
+game_props: Rc<RefCell<Dynamic>>, state_table: Rc<RefCell<Dynamic>>) {
+    engine.register_debugger(|_, debugger| debugger,
+    move |context, event, _, _, pos| {
+        if !DEBUGGER.with_borrow(|state| state.attached) {
+            return Ok(DebuggerCommand::Continue);
+        }
+        let (DebuggerEvent::Step, Some(line)) = (event, pos.line()) else {
+            return Ok(DebuggerCommand::StepInto);
+        };
+        let Some((row, _)) = RUNNING_ELEMENT.get() else {
+            return Ok(DebuggerCommand::StepInto);
+        };
+        let element_id = match row {
+            TableRow::Element(id, _) => id,
+            _ => 0,
+        };
+        let call_stack = context.global_runtime_state().debugger().call_stack();
+        let depth = call_stack.len();
+        // The line belongs to the script of the
+        // element which owns the running function
+        // (or to the element's own script, while
+        // it's body is running).
+        let script_id = match (call_stack.last(), element_defs.try_borrow()) {
+            (Some(frame), Ok(element_defs)) => match element_defs.get(&element_id) {
+                Some(Ok(definition)) => definition.fn_owner(&frame.fn_name),
+                _ => element_id,
+            },
+            _ => element_id,
+        };
+        // Decide if the debugger should break here.
+        let should_break = DEBUGGER.with_borrow_mut(|state| {
+            if state.last_break.is_some_and(|last| last != (script_id, line)) {
+                state.last_break = None;
+            }
+            let new_line = state.last_break.is_none();
+            let should_break = state.pause_requested ||
+            (new_line && state.breakpoints.contains(&(script_id, line))) ||
+            match state.stepping {
+                Some((StepMode::Into, _)) => new_line,
+                Some((StepMode::Over, from_depth)) => new_line && depth <= from_depth,
+                Some((StepMode::Out, from_depth)) => depth < from_depth,
+                None => false,
+            };
+            if should_break {
+                state.pause_requested = false;
+                state.stepping = None;
+                state.last_break = Some((script_id, line));
+            }
+            should_break
+        });
+        if !should_break {
+            return Ok(DebuggerCommand::StepInto);
+        }
+
+        // Report the paused script to the host.
+        let mut report = Map::new();
+        report.insert("element".into(), row.describe().into());
+        report.insert("element_id".into(), (element_id as rhai::INT).into());
+        report.insert("script_id".into(), (script_id as rhai::INT).into());
+        report.insert("line".into(), (line as rhai::INT).into());
+        report.insert("position".into(), (pos.position().unwrap_or(0) as rhai::INT).into());
+        report.insert("call_stack".into(), call_stack.iter().rev()
+        .map(|frame| Dynamic::from(frame.to_string())).collect::<rhai::Array>().into());
+        let mut scope = Map::new();
+        for (name, _, value) in context.scope().iter_raw() {
+            // Only the latest variable with a
+            // certain name is visible to the script.
+            if !scope.contains_key(name) {
+                scope.insert(name.into(), inspect(value).into());
+            }
+        }
+        report.insert("scope".into(), scope.into());
+        // An API value might be borrowed by the
+        // paused script (while it's being modified).
+        let mut api = Map::new();
+        for (name, value) in [("Scene", &scene_props), ("Game", &game_props), ("State", &state_table)] {
+            api.insert(name.into(), value.try_borrow()
+            .map_or_else(|_| String::from("<in use>"), |value| inspect(&value)).into());
+        }
+        report.insert("api".into(), api.into());
+
+        let command = debugger_break(&rhai::format_map_as_json(&report));
+        DEBUGGER.with_borrow_mut(|state| {
+            state.stepping = match command.as_str() {
+                "step-into" => Some((StepMode::Into, depth)),
+                "step-over" => Some((StepMode::Over, depth)),
+                "step-out" => Some((StepMode::Out, depth)),
+                _ => None,
+            };
+            if command == "detach" { state.attached = false; }
+        });
+        Ok(DebuggerCommand::StepInto)
+    });
+}
+
+/// Describes a value for the debugger's
+/// report, including the values of the\
+/// API types, which rhai can't describe.
+pub fn inspect(value: &Dynamic) -> String {
+    let value = value.flatten_clone();
+    if let Some(object) = value.read_lock::<element::Object>() {
+        return format!("{:?}", *object);
+    }
+    if let Some(scene) = value.read_lock::<element::Scene>() {
+        return format!("{:?}", *scene);
+    }
+    if let Some(game) = value.read_lock::<element::Game>() {
+        return format!("{:?}", *game);
+    }
+    if let Some(point) = value.read_lock::<element::ElemPoint>() {
+        return format!("{:?}", *point);
+    }
+    if let Some(color) = value.read_lock::<element::ElemColor>() {
+        return format!("{:?}", *color);
+    }
+    if let Some(sprites) = value.read_lock::<asset::AssetList<asset::Sprite>>() {
+        return format!("{:?}", *sprites);
+    }
+    format!("{:?}", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breakpoints_are_kept_once() {
+        debugger_clear_breakpoints();
+        debugger_set_breakpoint(2, 5);
+        debugger_set_breakpoint(2, 5);
+        debugger_set_breakpoint(0, 1);
+        assert_eq!(DEBUGGER.with_borrow(|state| state.breakpoints.clone()), [(2, 5), (0, 1)]);
+        debugger_remove_breakpoint(2, 5);
+        assert_eq!(DEBUGGER.with_borrow(|state| state.breakpoints.clone()), [(0, 1)]);
+        debugger_clear_breakpoints();
+        assert!(DEBUGGER.with_borrow(|state| state.breakpoints.is_empty()));
+    }
+
+    #[test]
+    fn detaching_cancels_the_pause() {
+        debugger_attach();
+        debugger_pause();
+        debugger_detach();
+        assert!(DEBUGGER.with_borrow(|state| !state.attached && !state.pause_requested && state.stepping.is_none()));
+    }
+
+    #[test]
+    fn inspects_api_values() {
+        let point = Dynamic::from(element::ElemPoint::new(1.0, 2.0));
+        assert_eq!(inspect(&point), format!("{:?}", element::ElemPoint::new(1.0, 2.0)));
+        assert_eq!(inspect(&Dynamic::from(5 as rhai::INT)), "5");
+    }
+}
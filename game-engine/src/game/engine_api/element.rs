@@ -3,73 +3,274 @@ use rhai::{Map, Dynamic};
 
 use crate::{data::get_element_type, game::dynamic_to_number};
 
-use super::asset::*;
+use super::{asset::*, random::{RandomStreams, Rng}};
 
 /// Receives a string borrow with a\
-/// hex color code (#RRGGBBAA / #RRGGBB),\
-/// and converts it into a slice of bytes.
-pub fn hex_color_to_rgba(hex: &str) -> [u8; 4] {
+/// hex color code (#RRGGBBAA / #RRGGBB /
+/// #RGBA / #RGB, the '#' is optional),\
+/// and converts it into a slice of bytes,
+/// or returns an error if it's malformed.
+pub fn parse_hex_color(hex: &str) -> Result<[u8; 4], String> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    if !digits.chars().all(|digit| digit.is_ascii_hexdigit()) {
+        return Err(format!("'{}' isn't a hex color code, because it contains a character which isn't a hex digit.", hex));
+    }
+    // A short code has a single
+    // digit for every channel.
+    let channel_len = match digits.len() {
+        3 | 4 => 1,
+        6 | 8 => 2,
+        _ => { return Err(format!("'{}' isn't a hex color code, because it should have 3, 4, 6 or 8 digits.", hex)); },
+    };
     // Result slice with
     // place-holder values
     let mut result: [u8; 4] = [0, 0, 0, 255];
-    // Result slice index counter
-    let mut i = 0;
-    // Hex color string
-    // index counter
-    let mut si = 1;
-
-    while si < hex.len() {
-        // Convert the hex string
-        // into an unsigned byte.
-        result[i] = u8::from_str_radix(&hex[si..si+2], 16)
-        .expect("hex to u8 parse should succeed");
-        // Increase hex color string
-        // index counter by 2.
-        si += 2;
-        // Increase result slice
-        // index counter by 1.
-        i += 1;
-    }
-    // Return the 
-    // result slice
-    result
+    for (i, channel) in digits.as_bytes().chunks(channel_len).enumerate() {
+        // The digits were checked already.
+        let value = u8::from_str_radix(std::str::from_utf8(channel).unwrap(), 16).unwrap();
+        result[i] = if channel_len == 1 { value * 17 } else { value };
+    }
+    Ok(result)
+}
+
+/// Checks every color attribute of an element's
+/// config (the state manager's 'clear-color',\
+/// and the 'color' of a scene's camera and of
+/// it's object instances), so a malformed color\
+/// is reported when the element is loaded, instead
+/// of when it's used.
+pub fn validate_config_colors(config: &Map) -> Result<(), String> {
+    let check = |value: &Dynamic, attribute: &str| match value.read_lock::<rhai::ImmutableString>() {
+        Some(hex) => parse_hex_color(&hex).map(|_| ())
+        .map_err(|err| format!("The '{}' attribute of the config is invalid: {}", attribute, err)),
+        None => Err(format!("The '{}' attribute of the config should be a string.", attribute)),
+    };
+    if let Some(value) = config.get("clear-color") {
+        check(value, "clear-color")?;
+    }
+    if let Some(value) = config.get("camera").and_then(|camera| camera.read_lock::<Map>()?.get("color").cloned()) {
+        check(&value, "camera.color")?;
+    }
+    if let Some(cameras) = config.get("cameras").and_then(|cameras| cameras.read_lock::<rhai::Array>()) {
+        for (idx, camera) in cameras.iter().enumerate() {
+            if let Some(value) = camera.read_lock::<Map>().and_then(|camera| camera.get("color").cloned()) {
+                check(&value, &format!("cameras[{}].color", idx))?;
+            }
+        }
+    }
+    if let Some(layers) = config.get("layers").and_then(|layers| layers.read_lock::<rhai::Array>()) {
+        for (idx, layer) in layers.iter().enumerate() {
+            if let Some(value) = layer.read_lock::<Map>().and_then(|layer| layer.get("tint").cloned()) {
+                check(&value, &format!("layers[{}].tint", idx))?;
+            }
+        }
+    }
+    if let Some(instances) = config.get("object-instances").and_then(|instances| instances.read_lock::<rhai::Array>()) {
+        for (idx, instance) in instances.iter().enumerate() {
+            if let Some(value) = instance.read_lock::<Map>().and_then(|instance| instance.get("color").cloned()) {
+                check(&value, &format!("object-instances[{}].color", idx))?;
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Used for storing point data\
 /// in element properties.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct ElemPoint {
     pub x: f32,
     pub y: f32,
 }
 
 impl ElemPoint {
-    pub fn get_x(&mut self) -> rhai::FLOAT { self.x.clone() as rhai::FLOAT }
-    pub fn get_y(&mut self) -> rhai::FLOAT { self.y.clone() as rhai::FLOAT }
+    pub fn get_x(&mut self) -> rhai::FLOAT { self.x as rhai::FLOAT }
+    pub fn get_y(&mut self) -> rhai::FLOAT { self.y as rhai::FLOAT }
+
+    pub fn set_x(&mut self, value: rhai::FLOAT) { self.x = value; }
+    pub fn set_y(&mut self, value: rhai::FLOAT) { self.y = value; }
 
-    pub fn set_x(&mut self, value: rhai::FLOAT) { self.x = value as f32; }
-    pub fn set_y(&mut self, value: rhai::FLOAT) { self.y = value as f32; }
+    // Constructors, for `point(x, y)`.
+    // Integer literals are accepted too,
+    // because rhai won't cast them.
+    pub fn new(x: rhai::FLOAT, y: rhai::FLOAT) -> Self { Self { x, y } }
+    pub fn new_int(x: rhai::INT, y: rhai::INT) -> Self { Self { x: x as f32, y: y as f32 } }
+    pub fn zero() -> Self { Self { x: 0.0, y: 0.0 } }
+
+    // Operators
+    pub fn add(a: Self, b: Self) -> Self { Self { x: a.x + b.x, y: a.y + b.y } }
+    pub fn sub(a: Self, b: Self) -> Self { Self { x: a.x - b.x, y: a.y - b.y } }
+    pub fn neg(a: Self) -> Self { Self { x: -a.x, y: -a.y } }
+    pub fn mul(a: Self, scalar: rhai::FLOAT) -> Self { Self { x: a.x * scalar, y: a.y * scalar } }
+    pub fn mul_int(a: Self, scalar: rhai::INT) -> Self { Self::mul(a, scalar as rhai::FLOAT) }
+    pub fn scalar_mul(scalar: rhai::FLOAT, a: Self) -> Self { Self::mul(a, scalar) }
+    pub fn scalar_mul_int(scalar: rhai::INT, a: Self) -> Self { Self::mul(a, scalar as rhai::FLOAT) }
+    pub fn div(a: Self, scalar: rhai::FLOAT) -> Self { Self { x: a.x / scalar, y: a.y / scalar } }
+    pub fn div_int(a: Self, scalar: rhai::INT) -> Self { Self::div(a, scalar as rhai::FLOAT) }
+    pub fn eq(a: Self, b: Self) -> bool { a.x == b.x && a.y == b.y }
+    pub fn ne(a: Self, b: Self) -> bool { !Self::eq(a, b) }
+
+    pub fn length(&mut self) -> rhai::FLOAT { self.x.hypot(self.y) as rhai::FLOAT }
+    /// Returns a point with the same direction
+    /// and a length of 1 (or (0, 0) if the
+    /// point's length is 0).
+    pub fn normalized(&mut self) -> Self {
+        let length = self.x.hypot(self.y);
+        if length == 0.0 { return Self::zero(); }
+        Self { x: self.x / length, y: self.y / length }
+    }
+    pub fn dot(&mut self, other: Self) -> rhai::FLOAT { (self.x * other.x + self.y * other.y) as rhai::FLOAT }
+    pub fn distance_to(&mut self, other: Self) -> rhai::FLOAT { (other.x - self.x).hypot(other.y - self.y) as rhai::FLOAT }
+    /// Returns the angle between the point and
+    /// the x axis, in the given unit (the API\
+    /// uses the unit of `Game.angle_unit`).
+    pub fn angle(&self, unit: AngleUnit) -> rhai::FLOAT { unit.radians_in_unit(self.y.atan2(self.x)) as rhai::FLOAT }
+    /// Returns the point rotated around (0, 0),
+    /// by an angle in the given unit (the API\
+    /// uses the unit of `Game.angle_unit`).
+    pub fn rotated(&self, angle: rhai::FLOAT, unit: AngleUnit) -> Self {
+        let (sin, cos) = unit.to_radians(angle).sin_cos();
+        Self { x: self.x * cos - self.y * sin, y: self.x * sin + self.y * cos }
+    }
+    /// Returns the point which is a `t`
+    /// fraction of the way to `other`.
+    pub fn lerp(&mut self, other: Self, t: rhai::FLOAT) -> Self {
+        Self { x: self.x + (other.x - self.x) * t, y: self.y + (other.y - self.y) * t }
+    }
+    /// Returns the point shortened to the
+    /// given length, if it's longer.
+    pub fn clamp_length(&mut self, max: rhai::FLOAT) -> Self {
+        let length = self.x.hypot(self.y);
+        if length <= max || length == 0.0 { return self.clone(); }
+        Self { x: self.x / length * max, y: self.y / length * max }
+    }
+    pub fn describe(&mut self) -> String { format!("({}, {})", self.x, self.y) }
 }
 
 /// Used for storing RGBA\
 /// color data in element\
 /// properties.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct ElemColor {
     pub r: u8, pub g: u8,
     pub b: u8, pub a: u8,
 }
 
 impl ElemColor {
-    pub fn get_r(&mut self) -> rhai::INT { self.r.clone() as rhai::INT }
-    pub fn get_g(&mut self) -> rhai::INT { self.g.clone() as rhai::INT }
-    pub fn get_b(&mut self) -> rhai::INT { self.b.clone() as rhai::INT }
-    pub fn get_a(&mut self) -> rhai::INT { self.a.clone() as rhai::INT }
+    pub fn get_r(&mut self) -> rhai::INT { self.r as rhai::INT }
+    pub fn get_g(&mut self) -> rhai::INT { self.g as rhai::INT }
+    pub fn get_b(&mut self) -> rhai::INT { self.b as rhai::INT }
+    pub fn get_a(&mut self) -> rhai::INT { self.a as rhai::INT }
 
     pub fn set_r(&mut self, value: rhai::INT) { self.r = value as u8; }
     pub fn set_g(&mut self, value: rhai::INT) { self.g = value as u8; }
     pub fn set_b(&mut self, value: rhai::INT) { self.b = value as u8; }
     pub fn set_a(&mut self, value: rhai::INT) { self.a = value as u8; }
+
+    /// Creates a color from a hex color
+    /// code, or from the name of a color in\
+    /// the palette (see `palette`).
+    pub fn from_hex(hex: &str) -> Result<Self, Box<rhai::EvalAltResult>> {
+        if let Some(color) = Self::palette().into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(hex)).map(|(_, color)| color) {
+            return Ok(color);
+        }
+        let [r, g, b, a] = parse_hex_color(hex)
+        .map_err(|err| format!("Tried to use 'color' with an invalid color: {}", err))?;
+        Ok(Self { r, g, b, a })
+    }
+    // Channels outside of
+    // 0-255 are clamped.
+    pub fn rgba(r: rhai::INT, g: rhai::INT, b: rhai::INT, a: rhai::INT) -> Self {
+        let channel = |value: rhai::INT| value.clamp(0, 255) as u8;
+        Self { r: channel(r), g: channel(g), b: channel(b), a: channel(a) }
+    }
+    pub fn rgb(r: rhai::INT, g: rhai::INT, b: rhai::INT) -> Self { Self::rgba(r, g, b, 255) }
+    /// Creates a color from a hue (in degrees),
+    /// a saturation and a value (from 0 to 1),\
+    /// and an alpha (from 0 to 255).
+    pub fn hsva(h: rhai::FLOAT, s: rhai::FLOAT, v: rhai::FLOAT, a: rhai::INT) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let chroma = v * s;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |value: f32| ((value + v - chroma) * 255.0).round() as u8;
+        Self { r: channel(r), g: channel(g), b: channel(b), a: a.clamp(0, 255) as u8 }
+    }
+    pub fn hsv(h: rhai::FLOAT, s: rhai::FLOAT, v: rhai::FLOAT) -> Self { Self::hsva(h, s, v, 255) }
+    // Integer literals are accepted
+    // for the hue, because rhai won't\
+    // cast them.
+    pub fn hsv_int(h: rhai::INT, s: rhai::FLOAT, v: rhai::FLOAT) -> Self { Self::hsv(h as rhai::FLOAT, s, v) }
+    pub fn hsva_int(h: rhai::INT, s: rhai::FLOAT, v: rhai::FLOAT, a: rhai::INT) -> Self {
+        Self::hsva(h as rhai::FLOAT, s, v, a)
+    }
+
+    /// Returns the color's hex color code
+    /// (#RRGGBBAA, or #RRGGBB if it's opaque).
+    pub fn to_hex(&self) -> String {
+        if self.a == 255 { format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b) }
+        else { format!("#{:02X}{:02X}{:02X}{:02X}", self.r, self.g, self.b, self.a) }
+    }
+    /// Returns the color's hue (in degrees),
+    /// saturation and value (from 0 to 1)\
+    /// as an array.
+    pub fn to_hsv(&self) -> rhai::Array {
+        let (r, g, b) = (self.r as f32 / 255.0, self.g as f32 / 255.0, self.b as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        let h = if chroma == 0.0 { 0.0 }
+        else if max == r { 60.0 * ((g - b) / chroma).rem_euclid(6.0) }
+        else if max == g { 60.0 * ((b - r) / chroma + 2.0) }
+        else { 60.0 * ((r - g) / chroma + 4.0) };
+        let s = if max == 0.0 { 0.0 } else { chroma / max };
+        vec![(h as rhai::FLOAT).into(), (s as rhai::FLOAT).into(), (max as rhai::FLOAT).into()]
+    }
+    /// Returns the color which is a `t` fraction
+    /// of the way to `other` (alpha included).
+    pub fn lerp(&mut self, other: Self, t: rhai::FLOAT) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t).round() as u8;
+        Self { r: channel(self.r, other.r), g: channel(self.g, other.g),
+            b: channel(self.b, other.b), a: channel(self.a, other.a) }
+    }
+    /// Multiplies two colors channel by
+    /// channel (like tinting a sprite).
+    pub fn mul(a: Self, b: Self) -> Self {
+        let channel = |x: u8, y: u8| ((x as u16 * y as u16 + 127) / 255) as u8;
+        Self { r: channel(a.r, b.r), g: channel(a.g, b.g), b: channel(a.b, b.b), a: channel(a.a, b.a) }
+    }
+    pub fn eq(a: Self, b: Self) -> bool { (a.r, a.g, a.b, a.a) == (b.r, b.g, b.b, b.a) }
+    pub fn ne(a: Self, b: Self) -> bool { !Self::eq(a, b) }
+
+    /// The named colors, which scripts can
+    /// use through the `Color` constant (like\
+    /// `Color.RED`), or by name with `color`.
+    pub fn palette() -> [(&'static str, Self); 13] {
+        [
+            ("WHITE", Self::rgb(255, 255, 255)),
+            ("BLACK", Self::rgb(0, 0, 0)),
+            ("GRAY", Self::rgb(128, 128, 128)),
+            ("RED", Self::rgb(255, 0, 0)),
+            ("GREEN", Self::rgb(0, 255, 0)),
+            ("BLUE", Self::rgb(0, 0, 255)),
+            ("YELLOW", Self::rgb(255, 255, 0)),
+            ("CYAN", Self::rgb(0, 255, 255)),
+            ("MAGENTA", Self::rgb(255, 0, 255)),
+            ("ORANGE", Self::rgb(255, 165, 0)),
+            ("PURPLE", Self::rgb(128, 0, 128)),
+            ("BROWN", Self::rgb(139, 69, 19)),
+            ("TRANSPARENT", Self::rgba(0, 0, 0, 0)),
+        ]
+    }
 }
 
 /// This struct is used for
@@ -90,6 +291,10 @@ pub struct ObjectInitInfo {
     pub init_x: f32, pub init_y: f32,
     pub init_scale_x: f32, pub init_scale_y: f32,
     pub init_color: String, pub init_alpha: u8,
+    pub init_rotation: f32,
+    pub init_z: f32,
+    pub init_visible: bool, pub init_active: bool,
+    pub init_props: Map,
 }
 
 impl ObjectInitInfo {
@@ -119,6 +324,26 @@ impl ObjectInitInfo {
         init_alpha: dynamic_to_number(&map["alpha"])
         .expect(concat!("Every instance in the 'object-instances' array",
         " of an scene's config should contain an integer 'alpha' attribute.")) as u8,
+        // The 'rotation' attribute is optional
+        // (in the unit of `Game.angle_unit`).
+        init_rotation: map.get("rotation").map_or(0.0, |rotation| dynamic_to_number(rotation)
+        .expect(concat!("The 'rotation' attribute of an instance in the 'object-instances'",
+        " array of an scene's config should be a float."))),
+        // The 'z' attribute is optional.
+        init_z: map.get("z").map_or(0.0, |z| dynamic_to_number(z)
+        .expect(concat!("The 'z' attribute of an instance in the 'object-instances'",
+        " array of an scene's config should be a float."))),
+        // The 'visible' and 'active' attributes are optional.
+        init_visible: map.get("visible").map(|visible| visible.as_bool()
+        .expect(concat!("The 'visible' attribute of an instance in the 'object-instances'",
+        " array of an scene's config should be a boolean."))).unwrap_or(true),
+        init_active: map.get("active").map(|active| active.as_bool()
+        .expect(concat!("The 'active' attribute of an instance in the 'object-instances'",
+        " array of an scene's config should be a boolean."))).unwrap_or(true),
+        // The 'properties' attribute is optional.
+        init_props: map.get("properties").map_or(Map::new(), |props| props.read_lock::<Map>()
+        .expect(concat!("The 'properties' attribute of an instance in the 'object-instances'",
+        " array of an scene's config should be an object-like attribute.")).clone()),
     } }
 }
 
@@ -127,26 +352,46 @@ impl ObjectInitInfo {
 /// and the local API which
 /// is used for accessing\
 /// and modifying them. 
-#[derive(Clone)]
+///
+/// An object which isn't visible isn't
+/// rendered, and the `update` function of\
+/// an object which isn't active isn't called
+/// (`init` still is), even if it's in a layer.
+#[derive(Clone, Debug)]
 pub struct Object {
     pub sprites: AssetList<Sprite>,
     pub position: ElemPoint,
     pub scale: ElemPoint,
     pub color: ElemColor,
+    pub rotation: f32,
+    pub z: f32,
+    pub visible: bool,
+    pub active: bool,
+    pub props: Map,
 
     pub index_in_stack: u32,
 }
 
 impl Object {
-    pub fn get_index_in_stack(&mut self) -> rhai::INT { self.index_in_stack.clone() as rhai::INT }
+    pub fn get_index_in_stack(&mut self) -> rhai::INT { self.index_in_stack as rhai::INT }
     pub fn get_position(&mut self) -> ElemPoint { self.position.clone() }
     pub fn get_scale(&mut self) -> ElemPoint { self.scale.clone() }
     pub fn get_color(&mut self) -> ElemColor { self.color.clone() }
+    pub fn get_rotation(&mut self) -> rhai::FLOAT { self.rotation as rhai::FLOAT }
+    pub fn get_z(&mut self) -> rhai::FLOAT { self.z as rhai::FLOAT }
+    pub fn get_visible(&mut self) -> bool { self.visible }
+    pub fn get_active(&mut self) -> bool { self.active }
     pub fn get_sprites(&mut self) -> AssetList<Sprite> { self.sprites.clone() }
+    pub fn get_props(&mut self) -> Map { self.props.clone() }
 
     pub fn set_position(&mut self, value: ElemPoint) { self.position = value; }
     pub fn set_scale(&mut self, value: ElemPoint) { self.scale = value; }
     pub fn set_color(&mut self, value: ElemColor) { self.color = value; }
+    pub fn set_rotation(&mut self, value: rhai::FLOAT) { self.rotation = value; }
+    pub fn set_z(&mut self, value: rhai::FLOAT) { self.z = value; }
+    pub fn set_visible(&mut self, value: bool) { self.visible = value; }
+    pub fn set_active(&mut self, value: bool) { self.active = value; }
+    pub fn set_props(&mut self, value: Map) { self.props = value; }
     /// `AssetList` setters need to
     /// check if the new list has\
     /// the same assets as the old
@@ -178,7 +423,8 @@ impl Object {
         }
         // Convert the hex color string
         // into a slice of bytes
-        let color = hex_color_to_rgba(&info.init_color);
+        let color = parse_hex_color(&info.init_color)
+        .expect("The color should have been checked when the scene's definition was loaded.");
         // Return the new `Object` API instance,
         // while setting its properties using
         // the provided object init info
@@ -194,10 +440,29 @@ impl Object {
             // to create a new `ElemColor` instance
             // for the object's color property
             color: ElemColor { r: color[0], g: color[1],
-                b: color[2], a: info.init_alpha }
+                b: color[2], a: info.init_alpha },
+            rotation: info.init_rotation,
+            z: info.init_z,
+            visible: info.init_visible,
+            active: info.init_active,
+            // Merge the object's default properties
+            // with the instance's own properties
+            props: Self::merge_props(config, info.init_props),
         }
     }
 
+    /// Merges the default properties from
+    /// the object's config with the properties\
+    /// provided for a specific instance, which
+    /// override the default ones.
+    fn merge_props(config: &Map, init_props: Map) -> Map {
+        // The 'properties' attribute is optional.
+        let mut props = config.get("properties").map_or(Map::new(), |props| props.read_lock::<Map>()
+        .expect("The 'properties' attribute of an object's config should be an object-like attribute.").clone());
+        props.extend(init_props);
+        props
+    }
+
     /// Using the object's config, and
     /// the provided object init info,\
     /// this function recycles an existing
@@ -210,7 +475,8 @@ impl Object {
         .expect("Every object's config should contain a 'sprites' array, which should only have integer members."));
         // Convert the hex color string
         // into a slice of bytes
-        let color = hex_color_to_rgba(&info.init_color);
+        let color = parse_hex_color(&info.init_color)
+        .expect("The color should have been checked when the scene's definition was loaded.");
         // Set the object's properties
         // using the provided object init info
         self.position.x = info.init_x;
@@ -223,6 +489,13 @@ impl Object {
         self.color.g = color[1];
         self.color.b = color[2];
         self.color.a = info.init_alpha;
+        self.rotation = info.init_rotation;
+        self.z = info.init_z;
+        self.visible = info.init_visible;
+        self.active = info.init_active;
+        // Merge the object's default properties
+        // with the instance's own properties
+        self.props = Self::merge_props(config, info.init_props);
     }
 }
 
@@ -233,15 +506,198 @@ impl Object {
 /// through by the renderer, an it will\
 /// draw the objects according to the order 
 /// of the layers they are placed in.
-#[derive(Clone)]
+///
+/// The objects on a screen space layer are
+/// positioned in pixels (from the top left\
+/// corner of the camera's viewport), without
+/// the camera's transform and tint.
+///
+/// The parallax is the fraction of the camera's
+/// movement the layer follows (0 keeps it in\
+/// place, like a far away background), and the
+/// opacity and tint apply to every object on it.
+///
+/// The objects on a layer are drawn in the
+/// order of the layer's sort mode, and the\
+/// objects whose sort keys are equal keep
+/// the order they were added in.
+#[derive(Clone, Debug)]
 pub struct Layer {
     pub name: String,
     pub instances: Vec<u32>,
+    pub screen_space: bool,
+    pub parallax: ElemPoint,
+    pub visible: bool,
+    pub opacity: f32,
+    pub tint: ElemColor,
+    pub blend_mode: BlendMode,
+    pub sort_mode: SortMode,
 }
 
 impl Layer {
     pub fn get_name(&mut self) -> String { self.name.clone() }
     pub fn get_instances(&mut self) -> Dynamic { self.instances.clone().into() }
+    pub fn get_screen_space(&mut self) -> bool { self.screen_space }
+    pub fn get_parallax(&mut self) -> ElemPoint { self.parallax.clone() }
+    pub fn get_visible(&mut self) -> bool { self.visible }
+    pub fn get_opacity(&mut self) -> rhai::FLOAT { self.opacity as rhai::FLOAT }
+    pub fn get_tint(&mut self) -> ElemColor { self.tint.clone() }
+    pub fn get_blend_mode(&mut self) -> String { String::from(self.blend_mode.name()) }
+    pub fn get_sort_mode(&mut self) -> String { String::from(self.sort_mode.name()) }
+
+    pub fn set_name(&mut self, value: String) { self.name = value; }
+    pub fn set_screen_space(&mut self, value: bool) { self.screen_space = value; }
+    pub fn set_parallax(&mut self, value: ElemPoint) { self.parallax = value; }
+    pub fn set_visible(&mut self, value: bool) { self.visible = value; }
+    pub fn set_opacity(&mut self, value: rhai::FLOAT) { self.opacity = value.clamp(0.0, 1.0); }
+    pub fn set_tint(&mut self, value: ElemColor) { self.tint = value; }
+    pub fn set_blend_mode(&mut self, value: String) -> Result<(), Box<rhai::EvalAltResult>> {
+        self.blend_mode = BlendMode::from_name(&value).ok_or_else(|| format!(concat!("'{}' isn't a blend mode",
+        " (the blend modes are 'normal', 'additive', 'multiply' and 'screen')."), value))?;
+        Ok(())
+    }
+    pub fn set_sort_mode(&mut self, value: String) -> Result<(), Box<rhai::EvalAltResult>> {
+        self.sort_mode = SortMode::from_name(&value).ok_or_else(|| format!(concat!("'{}' isn't a sort mode",
+        " (the sort modes are 'insertion', 'z' and 'y')."), value))?;
+        Ok(())
+    }
+
+    /// Creates a new empty layer
+    /// with the default properties.
+    pub fn named(name: String) -> Self {
+        Self {
+            name,
+            instances: Vec::new(),
+            screen_space: false,
+            parallax: ElemPoint { x: 1.0, y: 1.0 },
+            visible: true,
+            opacity: 1.0,
+            tint: ElemColor { r: 255, g: 255, b: 255, a: 255 },
+            blend_mode: BlendMode::Normal,
+            sort_mode: SortMode::Insertion,
+        }
+    }
+
+    /// Creates a new empty layer, using a member
+    /// of the 'layers' array in a scene's config,\
+    /// which is either the layer's name, or an
+    /// object with the layer's name and properties\
+    /// (every one of them is optional).
+    pub fn new(info: &Dynamic) -> Self {
+        if let Some(name) = info.read_lock::<rhai::ImmutableString>() {
+            return Self::named(name.to_string());
+        }
+        let info = info.read_lock::<Map>()
+        .expect("Every member of the 'layers' array in a scene's config should be a string or an object-like member.");
+        let mut layer = Self::named(info["name"].clone().into_string()
+        .expect("Every object-like member of the 'layers' array in a scene's config should have a 'name' string."));
+        if let Some(value) = info.get("screen-space") {
+            layer.screen_space = value.as_bool()
+            .expect("The 'screen-space' attribute of a layer in a scene's config should be a boolean.");
+        }
+        if let Some(value) = info.get("parallax") {
+            let parallax = value.read_lock::<Map>()
+            .expect("The 'parallax' attribute of a layer in a scene's config should be an object-like attribute.");
+            layer.parallax = ElemPoint {
+                x: dynamic_to_number(&parallax["x"]).expect("parallax.x should be a number."),
+                y: dynamic_to_number(&parallax["y"]).expect("parallax.y should be a number."),
+            };
+        }
+        if let Some(value) = info.get("visible") {
+            layer.visible = value.as_bool()
+            .expect("The 'visible' attribute of a layer in a scene's config should be a boolean.");
+        }
+        if let Some(value) = info.get("opacity") {
+            layer.opacity = dynamic_to_number(value)
+            .expect("The 'opacity' attribute of a layer in a scene's config should be a float.").clamp(0.0, 1.0);
+        }
+        if let Some(value) = info.get("tint") {
+            let [r, g, b, a] = parse_hex_color(&value.read_lock::<rhai::ImmutableString>()
+            .expect("The 'tint' attribute of a layer in a scene's config should be a string."))
+            .expect("The color should have been checked when the scene's definition was loaded.");
+            layer.tint = ElemColor { r, g, b, a };
+        }
+        if let Some(value) = info.get("blend-mode") {
+            layer.blend_mode = BlendMode::from_name(&value.read_lock::<rhai::ImmutableString>()
+            .expect("The 'blend-mode' attribute of a layer in a scene's config should be a string."))
+            .expect("The 'blend-mode' attribute of a layer should be 'normal', 'additive', 'multiply' or 'screen'.");
+        }
+        if let Some(value) = info.get("sort-mode") {
+            layer.sort_mode = SortMode::from_name(&value.read_lock::<rhai::ImmutableString>()
+            .expect("The 'sort-mode' attribute of a layer in a scene's config should be a string."))
+            .expect("The 'sort-mode' attribute of a layer should be 'insertion', 'z' or 'y'.");
+        }
+        layer
+    }
+}
+
+/// The orders the objects on a layer can be
+/// drawn in: the order they were added in,\
+/// the order of their `z` property, or the
+/// order of their y position (so the lower\
+/// objects are drawn over the higher ones,
+/// like in top-down games).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SortMode {
+    Insertion,
+    Z,
+    Y,
+}
+
+impl SortMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "insertion" => Some(Self::Insertion),
+            "z" => Some(Self::Z),
+            "y" => Some(Self::Y),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Insertion => "insertion",
+            Self::Z => "z",
+            Self::Y => "y",
+        }
+    }
+    /// Returns the key an object is sorted by.
+    pub fn key(&self, object: &Object) -> f32 {
+        match self {
+            Self::Insertion => 0.0,
+            Self::Z => object.z,
+            Self::Y => object.position.y,
+        }
+    }
+}
+
+/// The ways the objects on a layer
+/// can blend with what's behind them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BlendMode {
+    Normal,
+    Additive,
+    Multiply,
+    Screen,
+}
+
+impl BlendMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "normal" => Some(Self::Normal),
+            "additive" => Some(Self::Additive),
+            "multiply" => Some(Self::Multiply),
+            "screen" => Some(Self::Screen),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Additive => "additive",
+            Self::Multiply => "multiply",
+            Self::Screen => "screen",
+        }
+    }
 }
 
 /// This struct used for storing
@@ -256,21 +712,317 @@ impl Layer {
 /// to move in the opposite direction
 /// and when the camera's zoom is changed,\
 /// every object in the scene will appear to scale.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Camera {
     pub position: ElemPoint,
     pub zoom: f32,
+    pub rotation: f32,
     pub color: ElemColor,
+
+    // The area of the canvas the camera
+    // renders to (x, y, width and height,\
+    // as fractions of the canvas' size), and
+    // the names of the layers it renders\
+    // (`None` renders all of them).
+    pub viewport: [f32; 4],
+    pub layers: Option<Vec<String>>,
+
+    // The camera's behaviors, which
+    // the engine updates every frame
+    // (see `Camera::update`).
+    pub follow: Option<u32>,
+    pub smoothing: f32,
+    pub dead_zone: ElemPoint,
+    pub look_ahead: f32,
+    pub bounds: Option<[f32; 4]>,
+    pub trauma: f32,
+    pub shake_offset: ElemPoint,
+    pub shake_decay: f32,
+
+    // The state of the behaviors.
+    shake: ElemPoint,
+    last_target: Option<ElemPoint>,
+    shake_rng: Rng,
 }
 
 impl Camera {
+    /// Using the 'camera' object of a
+    /// scene's config, this function\
+    /// creates a new `Camera`.
+    ///
+    /// The behaviors are optional, and
+    /// they are all off by default.
+    pub fn new(camera_info: &Map) -> Self {
+        // Convert the hex color string
+        // into a slice of bytes
+        let color = parse_hex_color(&camera_info["color"].read_lock::<rhai::ImmutableString>()
+        .expect("Every 'camera' object in a scene's config should contain a 'color' string attribute."))
+        .expect("The color should have been checked when the scene's definition was loaded.");
+        let number = |name: &str, default: f32| camera_info.get(name).map_or(default, |value|
+        dynamic_to_number(value).unwrap_or_else(|_| panic!(
+        "The '{}' attribute of the 'camera' object in a scene's config should be a float.", name)));
+        let point = |name: &str| camera_info.get(name).map_or(ElemPoint::zero(), |value| {
+            let map = value.read_lock::<Map>().unwrap_or_else(|| panic!(
+            "The '{}' attribute of the 'camera' object in a scene's config should be an object-like attribute.", name));
+            ElemPoint {
+                x: map.get("x").map_or(0.0, |x| dynamic_to_number(x).expect("x should be a number.")),
+                y: map.get("y").map_or(0.0, |y| dynamic_to_number(y).expect("y should be a number.")),
+            }
+        });
+        let viewport = camera_info.get("viewport").map_or([0.0, 0.0, 1.0, 1.0], |value| {
+            let map = value.read_lock::<Map>()
+            .expect("The 'viewport' attribute of the 'camera' object in a scene's config should be an object-like attribute.");
+            ["x", "y", "width", "height"].map(|name| dynamic_to_number(&map[name])
+            .expect("The 'viewport' attribute of the 'camera' object should contain 'x', 'y', 'width' and 'height' floats."))
+        });
+        let layers = camera_info.get("layers").map(|value| value.clone().into_typed_array::<String>()
+        .expect("The 'layers' attribute of the 'camera' object in a scene's config should be an array of strings."));
+        let bounds = camera_info.get("bounds").map(|value| {
+            let map = value.read_lock::<Map>()
+            .expect("The 'bounds' attribute of the 'camera' object in a scene's config should be an object-like attribute.");
+            ["x1", "y1", "x2", "y2"].map(|name| dynamic_to_number(&map[name])
+            .expect("The 'bounds' attribute of the 'camera' object should contain 'x1', 'y1', 'x2' and 'y2' floats."))
+        });
+        Self {
+            position: ElemPoint {
+                x: dynamic_to_number(&camera_info["x"])
+                .expect("Every 'camera' object in a scene's config should contain a 'x' float attribute."),
+                y: dynamic_to_number(&camera_info["y"])
+                .expect("Every 'camera' object in a scene's config should contain a 'y' float attribute.")
+            },
+            zoom: dynamic_to_number(&camera_info["zoom"])
+            .expect("Every 'camera' object in a scene's config should contain a 'zoom' float attribute."),
+            // The 'rotation' attribute is optional.
+            rotation: number("rotation", 0.0),
+            color: ElemColor {
+                // Use the color slice of bytes
+                // to set the camera's color property
+                r: color[0],
+                g: color[1],
+                b: color[2],
+                a: dynamic_to_number(&camera_info["alpha"])
+                .expect(concat!("Every 'camera' object in a scene's config should",
+                " contain a 'alpha' integer attribute.")) as u8
+            },
+            viewport,
+            layers,
+            follow: camera_info.get("follow").map(|idx| dynamic_to_number(idx)
+            .expect("The 'follow' attribute of the 'camera' object in a scene's config should be an integer.") as u32),
+            smoothing: number("smoothing", 0.0),
+            dead_zone: point("dead-zone"),
+            look_ahead: number("look-ahead", 0.0),
+            bounds,
+            trauma: 0.0,
+            shake_offset: point("shake-offset"),
+            shake_decay: number("shake-decay", 1.0),
+            shake: ElemPoint::zero(),
+            last_target: None,
+            shake_rng: Rng::new(0),
+        }
+    }
+
     pub fn get_position(&mut self) -> ElemPoint { self.position.clone() }
-    pub fn get_zoom(&mut self) -> rhai::FLOAT { self.zoom.clone() as rhai::FLOAT }
+    pub fn get_zoom(&mut self) -> rhai::FLOAT { self.zoom as rhai::FLOAT }
+    pub fn get_rotation(&mut self) -> rhai::FLOAT { self.rotation as rhai::FLOAT }
     pub fn get_color(&mut self) -> ElemColor { self.color.clone() }
 
     pub fn set_position(&mut self, value: ElemPoint) { self.position = value; }
-    pub fn set_zoom(&mut self, value: rhai::FLOAT) { self.zoom = value as f32; }
+    pub fn set_zoom(&mut self, value: rhai::FLOAT) { self.zoom = value; }
+    pub fn set_rotation(&mut self, value: rhai::FLOAT) { self.rotation = value; }
     pub fn set_color(&mut self, value: ElemColor) { self.color = value; }
+
+    pub fn get_viewport(&mut self) -> Map {
+        let mut map = Map::new();
+        for (name, value) in ["x", "y", "width", "height"].iter().zip(self.viewport) {
+            map.insert((*name).into(), (value as rhai::FLOAT).into());
+        }
+        map
+    }
+    pub fn get_layers(&mut self) -> Dynamic {
+        self.layers.clone().map_or(Dynamic::UNIT, |layers| layers.into())
+    }
+    pub fn get_following(&mut self) -> rhai::INT { self.follow.map_or(-1, |idx| idx as rhai::INT) }
+    pub fn get_smoothing(&mut self) -> rhai::FLOAT { self.smoothing as rhai::FLOAT }
+    pub fn get_dead_zone(&mut self) -> ElemPoint { self.dead_zone.clone() }
+    pub fn get_look_ahead(&mut self) -> rhai::FLOAT { self.look_ahead as rhai::FLOAT }
+    pub fn get_trauma(&mut self) -> rhai::FLOAT { self.trauma as rhai::FLOAT }
+    pub fn get_shake_offset(&mut self) -> ElemPoint { self.shake_offset.clone() }
+    pub fn get_shake_decay(&mut self) -> rhai::FLOAT { self.shake_decay as rhai::FLOAT }
+
+    pub fn set_viewport(&mut self, value: Map) -> Result<(), Box<rhai::EvalAltResult>> {
+        let mut viewport = [0.0; 4];
+        for (name, member) in ["x", "y", "width", "height"].iter().zip(viewport.iter_mut()) {
+            *member = value.get(*name).and_then(|number| dynamic_to_number(number).ok()).ok_or_else(||
+            format!("A camera's viewport should contain '{}' as a number.", name))?;
+        }
+        self.viewport = viewport;
+        Ok(())
+    }
+    // `()` makes the camera render
+    // every layer of the scene.
+    pub fn set_layers(&mut self, value: Dynamic) -> Result<(), Box<rhai::EvalAltResult>> {
+        if value.is_unit() { self.layers = None; return Ok(()); }
+        self.layers = Some(value.into_typed_array::<String>()
+        .map_err(|_| "A camera's layers should be an array of strings, or ().")?);
+        Ok(())
+    }
+    pub fn set_smoothing(&mut self, value: rhai::FLOAT) { self.smoothing = value; }
+    pub fn set_dead_zone(&mut self, value: ElemPoint) { self.dead_zone = value; }
+    pub fn set_look_ahead(&mut self, value: rhai::FLOAT) { self.look_ahead = value; }
+    pub fn set_trauma(&mut self, value: rhai::FLOAT) { self.trauma = value.clamp(0.0, 1.0); }
+    pub fn set_shake_offset(&mut self, value: ElemPoint) { self.shake_offset = value; }
+    pub fn set_shake_decay(&mut self, value: rhai::FLOAT) { self.shake_decay = value; }
+
+    /// Returns true if the camera renders
+    /// the layer with the given name.
+    pub fn renders_layer(&self, name: &str) -> bool {
+        self.layers.as_ref().is_none_or(|layers| layers.iter().any(|layer| layer == name))
+    }
+
+    /// Returns the area of the canvas the camera
+    /// renders to, in pixels (x, y, width and\
+    /// height, from the canvas' top left corner).
+    pub fn pixel_viewport(&self, game: &Game) -> [f32; 4] {
+        let [x, y, width, height] = self.viewport;
+        let (left, top) = ((x * game.canvas_width).round(), (y * game.canvas_height).round());
+        [left, top, ((x + width) * game.canvas_width).round() - left,
+        ((y + height) * game.canvas_height).round() - top]
+    }
+
+    /// Makes the camera follow the object
+    /// instance at the given index (or stop\
+    /// following, if the index is negative).
+    pub fn follow(&mut self, idx: rhai::INT) {
+        self.follow = (idx >= 0).then_some(idx as u32);
+        self.last_target = None;
+    }
+    pub fn stop_following(&mut self) { self.follow(-1); }
+    pub fn set_bounds(&mut self, x1: rhai::FLOAT, y1: rhai::FLOAT, x2: rhai::FLOAT, y2: rhai::FLOAT) {
+        self.bounds = Some([x1, y1, x2, y2]);
+    }
+    pub fn clear_bounds(&mut self) { self.bounds = None; }
+    /// Adds trauma (the total is kept between
+    /// 0 and 1), which shakes the camera\
+    /// until it decays.
+    pub fn add_trauma(&mut self, amount: rhai::FLOAT) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// Updates the camera's behaviors, using
+    /// the position of the followed object (if\
+    /// there is one), the game's canvas size and
+    /// the time which elapsed (in milliseconds).
+    ///
+    /// The smoothing is the fraction of the way
+    /// to the target which is left after a second\
+    /// (0 moves the camera right away), and the
+    /// look-ahead is the time (in milliseconds)\
+    /// the camera looks ahead by, using the
+    /// target's velocity.
+    pub fn update(&mut self, target: Option<ElemPoint>, game: &Game, elapsed: f64) {
+        let seconds = (elapsed / 1000.0) as f32;
+        if let Some(target) = target {
+            let velocity = match (&self.last_target, seconds > 0.0) {
+                (Some(last), true) => ElemPoint { x: (target.x - last.x) / seconds, y: (target.y - last.y) / seconds },
+                _ => ElemPoint::zero(),
+            };
+            let goal = ElemPoint {
+                x: target.x + velocity.x * self.look_ahead / 1000.0,
+                y: target.y + velocity.y * self.look_ahead / 1000.0,
+            };
+            self.last_target = Some(target);
+            // The camera only moves when the
+            // goal leaves the dead zone.
+            let chase = |position: f32, goal: f32, dead_zone: f32| {
+                if goal - position > dead_zone { goal - dead_zone }
+                else if position - goal > dead_zone { goal + dead_zone }
+                else { position }
+            };
+            let desired = ElemPoint {
+                x: chase(self.position.x, goal.x, self.dead_zone.x.abs()),
+                y: chase(self.position.y, goal.y, self.dead_zone.y.abs()),
+            };
+            let t = if self.smoothing <= 0.0 { 1.0 } else { 1.0 - self.smoothing.min(0.9999).powf(seconds) };
+            self.position.x += (desired.x - self.position.x) * t;
+            self.position.y += (desired.y - self.position.y) * t;
+        }
+        // Keep the view inside the bounds, or
+        // centered on them if they're smaller.
+        if let (Some([x1, y1, x2, y2]), true) = (self.bounds, self.zoom != 0.0) {
+            let clamp = |position: f32, min: f32, max: f32, half_view: f32| {
+                if max - min < half_view * 2.0 { (min + max) / 2.0 }
+                else { position.clamp(min + half_view, max - half_view) }
+            };
+            let [_, _, width, height] = self.pixel_viewport(game);
+            self.position.x = clamp(self.position.x, x1, x2, width / self.zoom.abs() / 2.0);
+            self.position.y = clamp(self.position.y, y1, y2, height / self.zoom.abs() / 2.0);
+        }
+        // The shake grows with the square of the
+        // trauma, so it fades out smoothly.
+        self.trauma = (self.trauma - self.shake_decay * seconds).max(0.0);
+        let shake = self.trauma * self.trauma;
+        self.shake = ElemPoint {
+            x: self.shake_offset.x * shake * (self.shake_rng.float() as f32 * 2.0 - 1.0),
+            y: self.shake_offset.y * shake * (self.shake_rng.float() as f32 * 2.0 - 1.0),
+        };
+    }
+
+    /// Sets the zoom, while keeping the given
+    /// point (in the world) on the same spot\
+    /// on the screen, by moving the camera.
+    pub fn zoom_at(&mut self, point: ElemPoint, zoom: rhai::FLOAT) {
+        let (old_zoom, new_zoom) = (self.zoom.abs(), zoom.abs());
+        if old_zoom != 0.0 && new_zoom != 0.0 {
+            self.position.x = point.x - (point.x - self.position.x) * old_zoom / new_zoom;
+            self.position.y = point.y - (point.y - self.position.y) * old_zoom / new_zoom;
+        }
+        self.zoom = zoom;
+    }
+
+    /// Returns the view the renderer draws the
+    /// scene with: the camera's position (floored,\
+    /// so the pixels stay aligned), it's zoom, and
+    /// it's rotation in radians. The shake\
+    /// is included in the position.
+    ///
+    /// The renderer's uniforms and the conversions
+    /// between world and screen coordinates both\
+    /// use this view, so they always line up.
+    pub fn view(&self, unit: AngleUnit) -> ([f32; 2], f32, f32) {
+        ([(self.position.x + self.shake.x).floor(), (self.position.y + self.shake.y).floor()],
+        self.zoom.abs(), unit.to_radians(self.rotation))
+    }
+
+    /// Converts a point in the world to a point on
+    /// the canvas (in pixels, from it's top left\
+    /// corner), the same way the vertex shader does.
+    ///
+    /// The camera's position is at the center
+    /// of it's viewport on the canvas.
+    pub fn world_to_screen(&self, point: &ElemPoint, game: &Game) -> ElemPoint {
+        let (position, zoom, rotation) = self.view(game.angle_unit);
+        let [left, top, width, height] = self.pixel_viewport(game);
+        // The world rotates the other way
+        // around the camera on the screen.
+        let (sin, cos) = (-rotation).sin_cos();
+        let (x, y) = (point.x - position[0], point.y - position[1]);
+        ElemPoint {
+            x: (x * cos - y * sin) * zoom + left + width / 2.0,
+            y: (x * sin + y * cos) * zoom + top + height / 2.0,
+        }
+    }
+
+    /// Converts a point on the canvas to a point
+    /// in the world (see `world_to_screen`).
+    pub fn screen_to_world(&self, point: &ElemPoint, game: &Game) -> ElemPoint {
+        let (position, zoom, rotation) = self.view(game.angle_unit);
+        if zoom == 0.0 { return ElemPoint { x: position[0], y: position[1] }; }
+        let [left, top, width, height] = self.pixel_viewport(game);
+        let (sin, cos) = rotation.sin_cos();
+        let x = (point.x - left - width / 2.0) / zoom;
+        let y = (point.y - top - height / 2.0) / zoom;
+        ElemPoint { x: x * cos - y * sin + position[0], y: x * sin + y * cos + position[1] }
+    }
 }
 
 /// This struct defines the
@@ -278,9 +1030,13 @@ impl Camera {
 /// and the local API which
 /// is used for accessing\
 /// and modifying them.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Scene {
-    pub camera: Camera,
+    // The first camera is the scene's main
+    // camera (the `camera` property), and\
+    // the cameras are rendered in order, so
+    // the later ones draw over it.
+    pub cameras: Vec<Camera>,
 
     pub layers: Vec<Layer>,
     pub runtime_vacants: Vec<u32>,
@@ -291,13 +1047,52 @@ pub struct Scene {
 }
 
 impl Scene {
-    pub fn get_objects_len(&mut self) -> rhai::INT { self.objects_len.clone() as rhai::INT }
-    pub fn get_runtimes_len(&mut self) -> rhai::INT { self.runtimes_len.clone() as rhai::INT }
+    pub fn get_objects_len(&mut self) -> rhai::INT { self.objects_len as rhai::INT }
+    pub fn get_runtimes_len(&mut self) -> rhai::INT { self.runtimes_len as rhai::INT }
     pub fn get_runtime_vacants(&mut self)  -> Dynamic { self.runtime_vacants.clone().into() }
-    pub fn get_camera(&mut self) -> Camera { self.camera.clone() }
+    pub fn get_camera(&mut self) -> Camera { self.cameras[0].clone() }
+    pub fn get_cameras(&mut self) -> Dynamic { self.cameras.clone().into() }
     pub fn get_layers(&mut self) -> Dynamic { self.layers[0..self.layers_len].to_vec().into() }
 
-    pub fn set_camera(&mut self, value: Camera) { self.camera = value; }
+    pub fn set_camera(&mut self, value: Camera) { self.cameras[0] = value; }
+    // Only the layers' properties are set, and
+    // their instances are kept (they can only\
+    // change with `add_instance` and `remove_instance`).
+    pub fn set_layers(&mut self, value: Dynamic) -> Result<(), Box<rhai::EvalAltResult>> {
+        let value = value.try_cast::<rhai::Array>().ok_or("A scene's layers should be an array.")?;
+        if value.len() != self.layers_len {
+            return Err(format!("Tried to set {} layers on a scene which has {}.", value.len(), self.layers_len).into());
+        }
+        for (layer, new_layer) in self.layers.iter_mut().zip(value) {
+            let new_layer = new_layer.try_cast::<Layer>().ok_or("A scene's layers should all be 'Layer' values.")?;
+            *layer = Layer { instances: std::mem::take(&mut layer.instances), ..new_layer };
+        }
+        Ok(())
+    }
+    // A scene always keeps it's main camera.
+    pub fn set_cameras(&mut self, value: Dynamic) -> Result<(), Box<rhai::EvalAltResult>> {
+        let value = value.try_cast::<rhai::Array>().ok_or("A scene's cameras should be an array.")?;
+        if value.is_empty() { return Err("A scene should have at least one camera.".into()); }
+        self.cameras = value.into_iter().map(|camera| camera.try_cast::<Camera>())
+        .collect::<Option<Vec<Camera>>>().ok_or("A scene's cameras should all be 'Camera' values.")?;
+        Ok(())
+    }
+
+    /// Adds a camera to the scene, and
+    /// returns it's index in `cameras`.
+    pub fn add_camera(&mut self, camera: Camera) -> rhai::INT {
+        self.cameras.push(camera);
+        (self.cameras.len() - 1) as rhai::INT
+    }
+    /// Removes one of the scene's cameras
+    /// (but never the main one), and returns\
+    /// a boolean value which indicates if
+    /// the camera was removed.
+    pub fn remove_camera(&mut self, idx: rhai::INT) -> bool {
+        if idx <= 0 || idx as usize >= self.cameras.len() { return false; }
+        self.cameras.remove(idx as usize);
+        true
+    }
 
     /// This function removes an
     /// object instance from one of\
@@ -314,8 +1109,9 @@ impl Scene {
             // the instance is placed in the layer
             if let Some((index_to_remove, &_)) = self.layers[layer_idx].instances.iter()
             .enumerate().find(|&(_, &instance_index)| { instance_index as rhai::INT == idx }) {
-                // Remove the instance
-                let _ = self.layers[layer_idx].instances.swap_remove(index_to_remove);
+                // Remove the instance, while keeping
+                // the order of the other instances
+                let _ = self.layers[layer_idx].instances.remove(index_to_remove);
                 // Check if the instance 
                 // was a runtime object
                 if (idx as usize) >= self.objects_len && (idx as usize) < self.objects_len+self.runtimes_len {
@@ -358,6 +1154,99 @@ impl Scene {
         false
     }
 
+    /// This function moves an object instance
+    /// which is already in one of the scene's\
+    /// layers to the top of another layer. It
+    /// also returns a boolean value which\
+    /// indicates if the instance was moved.
+    pub fn move_instance(&mut self, idx: rhai::INT, layer_idx: rhai::INT) -> bool {
+        if layer_idx < 0 || layer_idx >= (self.layers_len as rhai::INT) { return false; }
+        // Find the layer in which the instance is
+        // placed, and the instance's index in it.
+        let Some((from_idx, index_in_layer)) = self.layers[0..self.layers_len].iter().enumerate()
+        .find_map(|(layer_idx, layer)| layer.instances.iter()
+        .position(|&instance_index| instance_index as rhai::INT == idx).map(|index| (layer_idx, index)))
+        else { return false; };
+        // Keep the order of the other instances
+        // in the layer the instance leaves.
+        let instance = self.layers[from_idx].instances.remove(index_in_layer);
+        self.layers[layer_idx as usize].instances.push(instance);
+        true
+    }
+
+    /// Returns the index of the first layer
+    /// with the given name, or -1 if the\
+    /// scene doesn't have such a layer.
+    pub fn layer_index(&mut self, name: &str) -> rhai::INT {
+        self.layers[0..self.layers_len].iter().position(|layer| layer.name == name)
+        .map_or(-1, |idx| idx as rhai::INT)
+    }
+
+    /// This function creates a new empty layer
+    /// at the given index (the layers from that\
+    /// index on move up by one), and returns a
+    /// boolean value which indicates if the\
+    /// layer was created.
+    pub fn create_layer(&mut self, name: String, at: rhai::INT) -> bool {
+        if at < 0 || at > (self.layers_len as rhai::INT) { return false; }
+        // Reuse the first layer which is left from
+        // a previous scene or a removed layer,\
+        // if there is one.
+        let layer = if self.layers_len < self.layers.len() {
+            let mut instances = std::mem::take(&mut self.layers.remove(self.layers_len).instances);
+            instances.clear();
+            Layer { instances, ..Layer::named(name) }
+        } else {
+            Layer::named(name)
+        };
+        self.layers.insert(at as usize, layer);
+        self.layers_len += 1;
+        true
+    }
+    /// Creates a new empty layer on
+    /// top of the scene's other layers.
+    pub fn create_top_layer(&mut self, name: String) -> bool {
+        let at = self.layers_len as rhai::INT;
+        self.create_layer(name, at)
+    }
+
+    /// This function removes one of the scene's
+    /// layers, and every object instance in it\
+    /// (see `remove_instance`). It also returns
+    /// a boolean value which indicates if the\
+    /// layer was removed.
+    pub fn remove_layer(&mut self, layer_idx: rhai::INT) -> bool {
+        if layer_idx < 0 || layer_idx >= (self.layers_len as rhai::INT) { return false; }
+        let mut layer = self.layers.remove(layer_idx as usize);
+        for &idx in &layer.instances {
+            // Add the runtime object instances
+            // to the "vacant runtime objects list"
+            if (idx as usize) >= self.objects_len && (idx as usize) < self.objects_len+self.runtimes_len {
+                self.runtime_vacants.push(idx);
+            }
+        }
+        // Keep the layer after the scene's layers,
+        // so it's memory can be reused (like when\
+        // the scene is recycled).
+        layer.instances.clear();
+        self.layers.push(layer);
+        self.layers_len -= 1;
+        true
+    }
+
+    /// This function moves one of the scene's
+    /// layers to the given index (the layers\
+    /// between the two indices move by one),
+    /// and returns a boolean value which\
+    /// indicates if the layer was moved.
+    pub fn move_layer(&mut self, layer_idx: rhai::INT, to: rhai::INT) -> bool {
+        let len = self.layers_len as rhai::INT;
+        if layer_idx < 0 || layer_idx >= len || to < 0 || to >= len { return false; }
+        let layer = self.layers.remove(layer_idx as usize);
+        self.layers.insert(to as usize, layer);
+        true
+    }
+
     /// Using the scene's config, this\
     /// function defines properties for\
     /// the scene in a new `Scene` API\
@@ -365,20 +1254,13 @@ impl Scene {
     pub fn new(config: &Map) -> Self {
         // Create a vector of `Layer` instances
         let mut layers_vec: Vec<Layer> = Vec::new();
-        // Add every layer whos name is
+        // Add every layer which is
         // included in the `layers` list
         // of the scene's config
-        for name in config["layers"].clone().into_typed_array::<String>()
-        .expect("Every scene's config should contain a 'layers' array, which should only have strings.") {
-            layers_vec.push( Layer { name, instances: { Vec::new() } } );
+        for info in &config["layers"].read_lock::<Vec<Dynamic>>()
+        .expect("Every scene's config should contain a 'layers' array.") as &Vec<Dynamic> {
+            layers_vec.push(Layer::new(info));
         }
-        // Get the camera's properties
-        let camera_info: &Map = &config["camera"].read_lock::<Map>()
-        .expect("Every scene's config should contain a 'camera' object-like attrbute.");
-        // Convert the hex color string
-        // into a slice of bytes
-        let color = hex_color_to_rgba(&camera_info["color"].read_lock::<rhai::ImmutableString>()
-        .expect("Every 'camera' object in a scene's config should contain a 'color' string attribute."));
         // Return the new `Scene` API instance,
         // while setting its properties using
         // the provided configuration
@@ -388,28 +1270,8 @@ impl Scene {
             layers_len: layers_vec.len(),
             runtimes_len: 0,
             runtime_vacants: Vec::new(),
-            // Create a new camera instance
-            // for the scene's `camera` property
-            camera: Camera {
-                position: ElemPoint { 
-                    x: dynamic_to_number(&camera_info["x"])
-                    .expect("Every 'camera' object in a scene's config should contain a 'x' float attribute."), 
-                    y: dynamic_to_number(&camera_info["y"])
-                    .expect("Every 'camera' object in a scene's config should contain a 'y' float attribute.") 
-                },
-                zoom: dynamic_to_number(&camera_info["zoom"])
-                .expect("Every 'camera' object in a scene's config should contain a 'zoom' float attribute."),
-                color: ElemColor {
-                    // Use the color slice of bytes
-                    // to set the camera's color property
-                    r: color[0],
-                    g: color[1],
-                    b: color[2],
-                    a: dynamic_to_number(&camera_info["alpha"])
-                    .expect(concat!("Every 'camera' object in a scene's config should",
-                    " contain a 'alpha' integer attribute.")) as u8
-                }
-            },
+            // Create the scene's cameras
+            cameras: Self::cameras_from_config(config),
             // Use the vector of `Layer` instances
             // as the scene's `layers` property
             layers: layers_vec,
@@ -427,38 +1289,35 @@ impl Scene {
         let mut i = 0_usize;
         // Iterate through the scene's config's
         // `layers` list, and add every layer
-        // whos name is included in the list
-        for name in &config["layers"].read_lock::<Vec<Dynamic>>()
-        .expect("Every scene's config should contain a 'layers' array, which should only have strings.")
-        as &Vec<Dynamic>  {
-            let name: &str = &name.read_lock::<rhai::ImmutableString>()
-            .expect("Every scene's config should contain a 'layers' array, which should only have strings.");
-            // If this layer name 's index
+        // which is included in the list
+        for info in &config["layers"].read_lock::<Vec<Dynamic>>()
+        .expect("Every scene's config should contain a 'layers' array.") as &Vec<Dynamic> {
+            // If this layer's index
             // is still in the bounds of
             // the scene's `layers` property,
             // then recycle the layer in its
             // index, replacing it with a new
-            // clear layer with the new name.
+            // clear layer (which keeps the
+            // memory of the old instances).
             if i < self.layers.len() {
-                self.layers[i].name.clear();
-                self.layers[i].name.push_str(name);
-                self.layers[i].instances.clear();
+                let mut instances = std::mem::take(&mut self.layers[i].instances);
+                instances.clear();
+                self.layers[i] = Layer { instances, ..Layer::new(info) };
                 i += 1;
                 continue;
             }
             // Otherwise, extend the scene's
             // `layers` property with a new
-            // clear layer with the new name.
-            self.layers.push( Layer {  name: String::from(name), instances: Vec::new() } );
+            // clear layer.
+            self.layers.push(Layer::new(info));
             i += 1;
         }
-        // Get the camera's properties
-        let camera_info: &Map = &config["camera"].read_lock::<Map>()
-        .expect("Every scene's config should contain a 'camera' object-like attrbute.");
-        // Convert the hex color string
-        // into a slice of bytes
-        let color = hex_color_to_rgba(&camera_info["color"].read_lock::<rhai::ImmutableString>()
-        .expect("Every 'camera' object in a scene's config should contain a 'color' string attribute."));
+        // Clear the layers which are left after
+        // the scene's layers, so they won't keep\
+        // instances of the previous scene.
+        for layer in self.layers[i..].iter_mut() {
+            layer.instances.clear();
+        }
         // Set the scene's properties
         // using the provided configuration
         self.layers_len = i;
@@ -466,28 +1325,78 @@ impl Scene {
         self.runtime_vacants.clear();
         self.objects_len = config["object-instances"].read_lock::<Vec<Dynamic>>()
         .expect("Every scene's config should contain an array 'object-instances' attribute.").len();
-        // Create a new camera instance
-        // for the scene's `camera` property
-        self.camera = Camera {
-            position: ElemPoint { 
-                x: dynamic_to_number(&camera_info["x"])
-                .expect("Every 'camera' object in a scene's config should contain a 'x' float attribute."), 
-                y: dynamic_to_number(&camera_info["y"])
-                .expect("Every 'camera' object in a scene's config should contain a 'y' float attribute.") 
-            },
-            zoom: dynamic_to_number(&camera_info["zoom"])
-            .expect("Every 'camera' object in a scene's config should contain a 'zoom' float attribute."),
-            color: ElemColor {
-                // Use the color slice of bytes
-                // to set the camera's color property
-                r: color[0],
-                g: color[1],
-                b: color[2],
-                a: dynamic_to_number(&camera_info["alpha"])
-                .expect(concat!("Every 'camera' object in a scene's config should",
-                " contain a 'alpha' integer attribute.")) as u8
+        // Create the scene's cameras
+        self.cameras = Self::cameras_from_config(config);
+    }
+
+    /// Creates the scene's cameras, using the
+    /// 'camera' object of the scene's config\
+    /// for the main camera, and the optional
+    /// 'cameras' array for the other ones.
+    fn cameras_from_config(config: &Map) -> Vec<Camera> {
+        let mut cameras = vec![Camera::new(&config["camera"].read_lock::<Map>()
+        .expect("Every scene's config should contain a 'camera' object-like attrbute."))];
+        if let Some(others) = config.get("cameras") {
+            for camera_info in &others.read_lock::<rhai::Array>()
+            .expect("The 'cameras' attribute of a scene's config should be an array.") as &rhai::Array {
+                cameras.push(Camera::new(&camera_info.read_lock::<Map>()
+                .expect("Every member of the 'cameras' array in a scene's config should be an object-like member.")));
             }
-        };
+        }
+        cameras
+    }
+}
+
+/// The number of rounds in which posted
+/// messages get delivered every frame,\
+/// if the state manager's config doesn't
+/// specify a 'max-message-rounds' attribute.
+pub const DEFAULT_MAX_MESSAGE_ROUNDS: u16 = 8;
+
+/// The seed of the game's random numbers,
+/// if the state manager's config doesn't\
+/// specify a 'seed' attribute. It's fixed,
+/// so every run of the game reproduces.
+pub const DEFAULT_SEED: u32 = 0;
+
+/// The unit of the angles in the
+/// API (like `Object.rotation`),\
+/// which is set by the state
+/// manager's 'angle-unit' config\
+/// attribute.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AngleUnit {
+    Degrees,
+    Radians,
+}
+
+impl AngleUnit {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "degrees" => Some(Self::Degrees),
+            "radians" => Some(Self::Radians),
+            _ => None,
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Degrees => "degrees",
+            Self::Radians => "radians",
+        }
+    }
+    /// Converts an angle in this unit to radians.
+    pub fn to_radians(self, angle: f32) -> f32 {
+        match self {
+            Self::Degrees => angle.to_radians(),
+            Self::Radians => angle,
+        }
+    }
+    /// Converts an angle in radians to this unit.
+    pub fn radians_in_unit(self, angle: f32) -> f32 {
+        match self {
+            Self::Degrees => angle.to_degrees(),
+            Self::Radians => angle,
+        }
     }
 }
 
@@ -496,7 +1405,7 @@ impl Scene {
 /// manager, and the local
 /// API which is used for\
 /// accessing and modifying them.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct Game {
     pub cur_scene: u32,
     pub canvas_width: f32,
@@ -506,6 +1415,9 @@ pub struct Game {
     pub clear_green: u8,
     pub clear_blue: u8,
     pub fps: u16,
+    pub max_message_rounds: u16,
+    pub angle_unit: AngleUnit,
+    pub random: RandomStreams,
 }
 
 impl Game {
@@ -517,6 +1429,9 @@ impl Game {
     pub fn get_clear_green(&mut self) -> rhai::INT { self.clear_green as rhai::INT }
     pub fn get_clear_blue(&mut self) -> rhai::INT { self.clear_blue as rhai::INT }
     pub fn get_fps(&mut self) -> rhai::INT { self.fps as rhai::INT }
+    pub fn get_max_message_rounds(&mut self) -> rhai::INT { self.max_message_rounds as rhai::INT }
+    pub fn get_seed(&mut self) -> rhai::INT { self.random.seed() as rhai::INT }
+    pub fn get_angle_unit(&mut self) -> String { String::from(self.angle_unit.name()) }
 
     // The `cur_scene` property
     // setter needs to check if
@@ -531,12 +1446,16 @@ impl Game {
             Err("Tried to switch to a scene that doesn't exist.".into())
         }
     }
-    pub fn set_canvas_width(&mut self, value: rhai::FLOAT) { self.canvas_width = value as f32; }
-    pub fn set_canvas_height(&mut self, value: rhai::FLOAT) { self.canvas_height = value as f32; }
+    pub fn set_canvas_width(&mut self, value: rhai::FLOAT) { self.canvas_width = value; }
+    pub fn set_canvas_height(&mut self, value: rhai::FLOAT) { self.canvas_height = value; }
     pub fn set_clear_red(&mut self, value: rhai::INT) { self.clear_red = value as u8; }
     pub fn set_clear_green(&mut self, value: rhai::INT) { self.clear_green = value as u8; }
     pub fn set_clear_blue(&mut self, value: rhai::INT) { self.clear_blue = value as u8; }
     pub fn set_fps(&mut self, value: rhai::INT) { self.fps = value as u16; }
+    pub fn set_max_message_rounds(&mut self, value: rhai::INT) { self.max_message_rounds = value.max(0) as u16; }
+    // Setting the seed restarts every
+    // random number stream from it.
+    pub fn set_seed(&mut self, value: rhai::INT) { self.random = RandomStreams::new(value as u32); }
 
     /// Using the state manager's\
     /// config, this function defines\
@@ -545,8 +1464,9 @@ impl Game {
     pub fn new(config: &Map) -> Self {
         // Convert the hex color string
         // into a slice of bytes
-        let color = hex_color_to_rgba(&config["clear-color"].read_lock::<rhai::ImmutableString>()
-        .expect("The state manager's config should contain a 'clear-color' string attribute."));
+        let color = parse_hex_color(&config["clear-color"].read_lock::<rhai::ImmutableString>()
+        .expect("The state manager's config should contain a 'clear-color' string attribute."))
+        .expect("The color should have been checked when the state manager's definition was loaded.");
         // Get the version numbers as
         // an vector of `Dynamic`s.
         let version_vec: &Vec<Dynamic> = &config["version"].read_lock::<Vec<Dynamic>>()
@@ -558,11 +1478,25 @@ impl Game {
             cur_scene: dynamic_to_number(&config["initial-scene"])
             .expect("The state manager's config should contain a 'initial-scene' integer attribute.") as u32,
             canvas_width: dynamic_to_number(&config["canvas-width"])
-            .expect("The state manager's config should contain a 'canvas-width' float attribute.") as f32,
+            .expect("The state manager's config should contain a 'canvas-width' float attribute."),
             canvas_height: dynamic_to_number(&config["canvas-height"])
-            .expect("The state manager's config should contain a 'canvas-height' float attribute.") as f32,
+            .expect("The state manager's config should contain a 'canvas-height' float attribute."),
             fps: dynamic_to_number(&config["fps"])
             .expect("The state manager's config should contain a 'fps' integer attribute.") as u16,
+            // The 'max-message-rounds' attribute is optional,
+            // and it limits the number of rounds in which
+            // posted messages get delivered every frame.
+            max_message_rounds: config.get("max-message-rounds").map_or(DEFAULT_MAX_MESSAGE_ROUNDS,
+            |rounds| dynamic_to_number(rounds)
+            .expect("The state manager's config 'max-message-rounds' attribute should be an integer.") as u16),
+            // The 'angle-unit' attribute is optional.
+            angle_unit: config.get("angle-unit").map_or(AngleUnit::Degrees, |unit| AngleUnit::from_name(
+            &unit.clone().into_string().expect("The state manager's config 'angle-unit' attribute should be a string."))
+            .expect("The state manager's config 'angle-unit' attribute should be 'degrees' or 'radians'.")),
+            // The 'seed' attribute is optional.
+            random: RandomStreams::new(config.get("seed").map_or(DEFAULT_SEED,
+            |seed| seed.as_int()
+            .expect("The state manager's config 'seed' attribute should be an integer.") as u32)),
             // Use the version numbers vector
             // to set the state manager's `version``
             // property
@@ -583,4 +1517,279 @@ impl Game {
             clear_blue: color[2],
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_hex_length() {
+        assert_eq!(parse_hex_color("#F80"), Ok([255, 136, 0, 255]));
+        assert_eq!(parse_hex_color("F808"), Ok([255, 136, 0, 136]));
+        assert_eq!(parse_hex_color("#12AB9f"), Ok([0x12, 0xAB, 0x9F, 255]));
+        assert_eq!(parse_hex_color("12ab9f40"), Ok([0x12, 0xAB, 0x9F, 0x40]));
+    }
+
+    #[test]
+    fn rejects_malformed_hex() {
+        assert!(parse_hex_color("").is_err());
+        assert!(parse_hex_color("#12").is_err());
+        assert!(parse_hex_color("#12345").is_err());
+        assert!(parse_hex_color("#123456789").is_err());
+        assert!(parse_hex_color("#GG0000").is_err());
+        assert!(parse_hex_color("##F80").is_err());
+    }
+
+    #[test]
+    fn formats_hex() {
+        assert_eq!(ElemColor::rgb(255, 136, 0).to_hex(), "#FF8800");
+        assert_eq!(ElemColor::rgba(255, 136, 0, 64).to_hex(), "#FF880040");
+        // Channels outside of 0-255 are clamped.
+        assert_eq!(ElemColor::rgba(300, -5, 0, 255).to_hex(), "#FF0000");
+    }
+
+    #[test]
+    fn hsv_round_trip() {
+        for [r, g, b] in [[0, 0, 0], [255, 255, 255], [255, 0, 0], [0, 255, 0], [0, 0, 255],
+        [255, 136, 0], [18, 171, 159], [128, 64, 200]] {
+            let color = ElemColor::rgb(r, g, b);
+            let hsv: Vec<rhai::FLOAT> = color.to_hsv().into_iter().map(|value| value.as_float().unwrap()).collect();
+            let back = ElemColor::hsv(hsv[0], hsv[1], hsv[2]);
+            assert_eq!((back.r, back.g, back.b), (color.r, color.g, color.b), "{:?} -> {:?}", color, hsv);
+        }
+        let red = ElemColor::hsv(360.0, 1.0, 1.0);
+        assert_eq!((red.r, red.g, red.b), (255, 0, 0));
+    }
+
+    #[test]
+    fn point_math() {
+        let close = |a: rhai::FLOAT, b: rhai::FLOAT| (a - b).abs() < 1e-5;
+        let mut point = ElemPoint::new(3.0, 4.0);
+        assert!(close(point.length(), 5.0));
+        let normalized = point.normalized();
+        assert!(close(normalized.x, 0.6) && close(normalized.y, 0.8));
+        assert!(ElemPoint::zero().normalized().x == 0.0);
+        assert!(close(point.distance_to(ElemPoint::zero()), 5.0));
+        assert!(close(point.dot(ElemPoint::new(1.0, 0.0)), 3.0));
+        assert!(ElemPoint::eq(ElemPoint::add(point.clone(), ElemPoint::neg(point.clone())), ElemPoint::zero()));
+        let rotated = ElemPoint::new(1.0, 0.0).rotated(90.0, AngleUnit::Degrees);
+        assert!(close(rotated.x, 0.0) && close(rotated.y, 1.0));
+        let rotated = ElemPoint::new(1.0, 0.0).rotated(std::f32::consts::PI, AngleUnit::Radians);
+        assert!(close(rotated.x, -1.0) && close(rotated.y, 0.0));
+        assert!(close(ElemPoint::new(0.0, 2.0).angle(AngleUnit::Degrees), 90.0));
+        assert!(close(ElemPoint::new(0.0, 2.0).angle(AngleUnit::Radians), std::f32::consts::FRAC_PI_2));
+    }
+
+    /// Parses a JSON config into a map.
+    fn json(json: &str) -> Map {
+        rhai::Engine::new().parse_json(json, false).expect("The test's JSON should be valid.")
+    }
+
+    #[test]
+    fn parses_angle_units_and_rotations() {
+        assert_eq!(AngleUnit::from_name("degrees").map(|unit| unit.name()), Some("degrees"));
+        assert_eq!(AngleUnit::from_name("radians").map(|unit| unit.name()), Some("radians"));
+        assert!(AngleUnit::from_name("turns").is_none());
+        assert_eq!(AngleUnit::Degrees.to_radians(180.0), std::f32::consts::PI);
+        assert_eq!(AngleUnit::Radians.radians_in_unit(1.5), 1.5);
+        let instance = json(r##"{"x": 0, "y": 0, "scale-x": 1, "scale-y": 1, "color": "#FFFFFF", "alpha": 255}"##);
+        assert_eq!(ObjectInitInfo::new(0, &instance).init_rotation, 0.0);
+        let mut rotated = instance.clone();
+        rotated.insert("rotation".into(), (45 as rhai::INT).into());
+        assert_eq!(ObjectInitInfo::new(0, &rotated).init_rotation, 45.0);
+    }
+
+    /// The state manager of a 960x540 game.
+    fn test_game() -> Game {
+        Game::new(&json(crate::game::TEST_CONFIG))
+    }
+
+    /// Returns a camera with the given extra
+    /// attributes (like the 'viewport').
+    fn camera(x: f32, y: f32, zoom: f32, extra: &str) -> Camera {
+        Camera::new(&json(&format!(r##"{{"x": {:.1}, "y": {:.1}, "zoom": {:.1}, "color": "#FFFFFF", "alpha": 255{}}}"##,
+        x, y, zoom, extra)))
+    }
+
+    fn assert_close(a: &ElemPoint, b: &ElemPoint) {
+        assert!((a.x - b.x).abs() < 1e-3 && (a.y - b.y).abs() < 1e-3, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn world_and_screen_round_trip() {
+        let game = test_game();
+        let plain = camera(0.0, 0.0, 1.0, "");
+        // The camera's position is at the center of the canvas.
+        assert_close(&plain.world_to_screen(&ElemPoint::zero(), &game), &ElemPoint::new(480.0, 270.0));
+        let turned = camera(100.0, -50.0, 2.0, r#", "rotation": 30"#);
+        let split = camera(10.0, 20.0, 0.5, r#", "rotation": 90, "viewport": {"x": 0.5, "y": 0, "width": 0.5, "height": 1}"#);
+        for camera in [plain, turned, split] {
+            for point in [ElemPoint::zero(), ElemPoint::new(123.0, -45.5), ElemPoint::new(-300.0, 200.0)] {
+                let screen = camera.world_to_screen(&point, &game);
+                assert_close(&camera.screen_to_world(&screen, &game), &point);
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_at_keeps_the_point_in_place() {
+        let game = test_game();
+        let mut camera = camera(40.0, 10.0, 1.0, "");
+        let point = ElemPoint::new(200.0, 100.0);
+        let before = camera.world_to_screen(&point, &game);
+        camera.zoom_at(point.clone(), 4.0);
+        assert_eq!(camera.zoom, 4.0);
+        // The view's position is floored, so
+        // the point can move by a pixel.
+        let after = camera.world_to_screen(&point, &game);
+        assert!((after.x - before.x).abs() <= 4.0 && (after.y - before.y).abs() <= 4.0, "{:?} -> {:?}", before, after);
+    }
+
+    #[test]
+    fn camera_follows_outside_of_the_dead_zone() {
+        let game = test_game();
+        let mut camera = camera(0.0, 0.0, 1.0, r#", "dead-zone": {"x": 10, "y": 10}"#);
+        camera.update(Some(ElemPoint::new(5.0, -5.0)), &game, 16.0);
+        assert_close(&camera.position, &ElemPoint::zero());
+        camera.update(Some(ElemPoint::new(30.0, -5.0)), &game, 16.0);
+        assert_close(&camera.position, &ElemPoint::new(20.0, 0.0));
+    }
+
+    #[test]
+    fn camera_stays_inside_the_bounds() {
+        let game = test_game();
+        let mut camera = camera(0.0, 0.0, 1.0, r#", "bounds": {"x1": 0, "y1": 0, "x2": 2000, "y2": 1000}"#);
+        camera.update(None, &game, 16.0);
+        assert_close(&camera.position, &ElemPoint::new(480.0, 270.0));
+        // Bounds which are smaller than the
+        // view keep the camera at their center.
+        camera.set_bounds(0.0, 0.0, 100.0, 2000.0);
+        camera.position = ElemPoint::new(0.0, 5000.0);
+        camera.update(None, &game, 16.0);
+        assert_close(&camera.position, &ElemPoint::new(50.0, 1730.0));
+    }
+
+    #[test]
+    fn camera_shake_fades_out() {
+        let game = test_game();
+        let mut camera = camera(0.0, 0.0, 1.0, r#", "shake-offset": {"x": 8, "y": 8}, "shake-decay": 2"#);
+        camera.trauma = 1.0;
+        camera.update(None, &game, 250.0);
+        assert_eq!(camera.trauma, 0.5);
+        assert!(camera.shake.x.abs() <= 2.0 && camera.shake.y.abs() <= 2.0);
+        camera.update(None, &game, 1000.0);
+        assert_eq!((camera.trauma, camera.shake.x, camera.shake.y), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn scenes_keep_their_main_camera() {
+        let mut config = json(crate::game::TEST_SCENE_CONFIG);
+        config.insert("cameras".into(), Dynamic::from_array(vec![Dynamic::from_map(json(concat!(
+        r##"{"x": 0, "y": 0, "zoom": 1, "color": "#FFFFFF", "alpha": 255, "layers": ["layer 1"], "##,
+        r##""viewport": {"x": 0.75, "y": 0, "width": 0.25, "height": 0.25}}"##)))]));
+        let mut scene = Scene::new(&config);
+        assert_eq!(scene.cameras.len(), 2);
+        assert_eq!(scene.cameras[1].layers, Some(vec![String::from("layer 1")]));
+        assert_eq!(scene.cameras[1].pixel_viewport(&test_game()), [720.0, 0.0, 240.0, 135.0]);
+        assert_eq!(scene.cameras[0].pixel_viewport(&test_game()), [0.0, 0.0, 960.0, 540.0]);
+        assert_eq!(scene.add_camera(camera(0.0, 0.0, 1.0, "")), 2);
+        assert!(!scene.remove_camera(0) && !scene.remove_camera(3));
+        assert!(scene.remove_camera(1) && scene.cameras.len() == 2);
+        assert!(scene.set_cameras(Dynamic::from_array(Vec::new())).is_err());
+    }
+
+    /// Returns a layer, from a member of
+    /// the 'layers' array of a scene's config.
+    fn layer(info: &str) -> Layer {
+        Layer::new(&rhai::Engine::new().parse_json(format!(r#"{{"layer": {}}}"#, info), false)
+        .expect("The test's JSON should be valid.")["layer"])
+    }
+
+    #[test]
+    fn layers_can_be_in_screen_space() {
+        assert!(!layer(r#""world""#).screen_space);
+        assert!(!layer(r#"{"name": "world"}"#).screen_space);
+        let ui = layer(r#"{"name": "ui", "screen-space": true}"#);
+        assert!(ui.screen_space && ui.name == "ui");
+    }
+
+    #[test]
+    fn parses_the_layers_properties() {
+        for mode in [BlendMode::Normal, BlendMode::Additive, BlendMode::Multiply, BlendMode::Screen] {
+            assert_eq!(BlendMode::from_name(mode.name()), Some(mode));
+        }
+        assert!(BlendMode::from_name("Normal").is_none());
+        let plain = layer(r#""plain""#);
+        assert_eq!((plain.visible, plain.opacity, plain.blend_mode), (true, 1.0, BlendMode::Normal));
+        let layer = layer(concat!(r##"{"name": "fog", "parallax": {"x": 0.5, "y": 0.25}, "visible": false, "##,
+        r##""opacity": 1.5, "tint": "#FF000080", "blend-mode": "screen"}"##));
+        assert_eq!((layer.parallax.x, layer.parallax.y), (0.5, 0.25));
+        // The opacity is clamped to 0-1.
+        assert_eq!((layer.visible, layer.opacity, layer.blend_mode), (false, 1.0, BlendMode::Screen));
+        assert_eq!((layer.tint.r, layer.tint.g, layer.tint.a), (255, 0, 128));
+    }
+
+    /// Returns a scene with two layers ("back" and
+    /// "front"), 3 instances and 2 runtime objects.
+    fn two_layer_scene() -> Scene {
+        let mut scene = Scene::new(&json(crate::game::TEST_SCENE_CONFIG
+        .replace(r#"["layer 1"]"#, r#"["back", "front"]"#).as_str()));
+        (scene.objects_len, scene.runtimes_len) = (3, 2);
+        for idx in 0..5 {
+            assert!(scene.add_instance(idx, 0));
+        }
+        scene
+    }
+
+    fn instances(scene: &Scene) -> Vec<Vec<u32>> {
+        scene.layers[0..scene.layers_len].iter().map(|layer| layer.instances.clone()).collect()
+    }
+
+    #[test]
+    fn removing_instances_keeps_the_order() {
+        let mut scene = two_layer_scene();
+        assert!(scene.remove_instance(1) && scene.remove_instance(3));
+        assert!(!scene.remove_instance(1));
+        assert_eq!(instances(&scene), [vec![0, 2, 4], vec![]]);
+        // Only the runtime object's index is vacant.
+        assert_eq!(scene.runtime_vacants, [3]);
+        assert!(scene.add_instance(3, 1) && scene.runtime_vacants.is_empty());
+        assert!(scene.move_instance(0, 1));
+        assert_eq!(instances(&scene), [vec![2, 4], vec![3, 0]]);
+    }
+
+    #[test]
+    fn layers_are_created_removed_and_moved() {
+        let mut scene = two_layer_scene();
+        assert!(scene.create_layer(String::from("middle"), 1));
+        assert!(!scene.create_layer(String::from("nowhere"), 4));
+        assert_eq!((scene.layer_index("middle"), scene.layer_index("front"), scene.layer_index("none")), (1, 2, -1));
+        assert!(scene.move_layer(0, 2));
+        assert_eq!(scene.layer_index("back"), 2);
+        // Removing a layer removes it's instances.
+        assert!(scene.remove_layer(2));
+        assert_eq!(scene.layers_len, 2);
+        assert_eq!(scene.runtime_vacants, [3, 4]);
+        // The removed layer's memory is reused.
+        assert!(scene.create_top_layer(String::from("top")));
+        assert_eq!((scene.layers.len(), scene.layer_index("top")), (3, 2));
+        assert!(scene.layers[2].instances.is_empty());
+    }
+
+    #[test]
+    fn parses_the_sort_modes() {
+        for mode in [SortMode::Insertion, SortMode::Z, SortMode::Y] {
+            assert_eq!(SortMode::from_name(mode.name()), Some(mode));
+        }
+        assert!(SortMode::from_name("x").is_none());
+        assert_eq!(layer(r#"{"name": "ground", "sort-mode": "y"}"#).sort_mode, SortMode::Y);
+        let mut plain = layer(r#""plain""#);
+        assert_eq!(plain.sort_mode, SortMode::Insertion);
+        assert!(plain.set_sort_mode(String::from("z")).is_ok() && plain.sort_mode == SortMode::Z);
+        assert!(plain.set_sort_mode(String::from("depth")).is_err() && plain.sort_mode == SortMode::Z);
+        let object = Object::new(&json(r#"{"sprites": []}"#), ObjectInitInfo::new(0,
+        &json(r##"{"x": 3, "y": 7, "scale-x": 1, "scale-y": 1, "color": "#FFFFFF", "alpha": 255, "z": -2}"##)));
+        assert_eq!([SortMode::Insertion, SortMode::Z, SortMode::Y].map(|mode| mode.key(&object)), [0.0, -2.0, 7.0]);
+    }
+}
@@ -0,0 +1,245 @@
+use std::{cell::RefCell, collections::VecDeque};
+
+use wasm_bindgen::prelude::*;
+use rhai::{Map, Dynamic};
+
+use crate::game::dynamic_to_number;
+
+use super::RUNNING_ELEMENT;
+
+/// The number of entries the log keeps, if
+/// the state manager's config doesn't\
+/// specify a 'log-capacity' attribute.
+pub const DEFAULT_LOG_CAPACITY: usize = 256;
+
+/// The severity of a log entry.\
+/// Entries below the log's minimum
+/// level are dropped.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Parses a level's name ("debug",
+    /// "info", "warn" or "error").
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "debug" => Some(Self::Debug),
+            "info" => Some(Self::Info),
+            "warn" => Some(Self::Warn),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+    /// Returns the level's name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// A single message in the log, tagged with
+/// the element which logged it (if any),\
+/// and the frame and game time (in
+/// milliseconds) it was logged on.
+#[derive(Clone)]
+pub struct LogEntry {
+    pub level: LogLevel,
+    pub element: Option<String>,
+    pub frame: u32,
+    pub time: f64,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// Converts the entry into a rhai map,
+    /// for the in-game API and the host.
+    fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.insert("level".into(), self.level.name().into());
+        map.insert("element".into(), self.element.clone().map_or(Dynamic::UNIT, Dynamic::from));
+        map.insert("frame".into(), (self.frame as rhai::INT).into());
+        map.insert("time".into(), (self.time as rhai::FLOAT).into());
+        map.insert("message".into(), self.message.clone().into());
+        map
+    }
+}
+
+/// A struct that stores the log's
+/// entries in a ring buffer, and\
+/// keeps track of the game's frames.
+struct Logger {
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+    min_level: LogLevel,
+    frame: u32,
+    time: f64,
+}
+
+thread_local! {
+    static LOG: RefCell<Logger> = const { RefCell::new(Logger {
+        entries: VecDeque::new(),
+        capacity: DEFAULT_LOG_CAPACITY,
+        min_level: LogLevel::Debug,
+        frame: 0,
+        time: 0_f64,
+    }) };
+}
+
+/// Configures the log using the state
+/// manager's config, which might specify\
+/// a minimum level ('log-level') and a
+/// capacity ('log-capacity').
+pub fn configure(config: &Map) {
+    let min_level = config.get("log-level").map_or(LogLevel::Debug, |level| LogLevel::from_name(
+    &level.clone().into_string().expect("The state manager's config 'log-level' attribute should be a string."))
+    .expect("The state manager's config 'log-level' attribute should be 'debug', 'info', 'warn' or 'error'."));
+    let capacity = config.get("log-capacity").map_or(DEFAULT_LOG_CAPACITY, |capacity|
+    dynamic_to_number(capacity)
+    .expect("The state manager's config 'log-capacity' attribute should be an integer.") as usize);
+    LOG.with_borrow_mut(|log| {
+        log.min_level = min_level;
+        log.capacity = capacity;
+        while log.entries.len() > capacity { log.entries.pop_front(); }
+    });
+}
+
+/// Moves the log to the next frame, which
+/// took `elapsed` milliseconds of game time.
+pub fn advance_frame(elapsed: f64) {
+    LOG.with_borrow_mut(|log| {
+        log.frame += 1;
+        log.time += elapsed;
+    });
+}
+
+/// Logs a message, tagged with the element which
+/// owns the running callback (if any), and sends\
+/// it to the browser's console (or to stderr,
+/// when the game runs headless).
+pub fn log(level: LogLevel, message: &str) {
+    let element = RUNNING_ELEMENT.get().map(|(row, _)| row.describe());
+    let Some(entry) = LOG.with_borrow_mut(|log| {
+        if level < log.min_level || log.capacity == 0 { return None; }
+        let entry = LogEntry { level, element, frame: log.frame, time: log.time, message: String::from(message) };
+        if log.entries.len() >= log.capacity { log.entries.pop_front(); }
+        log.entries.push_back(entry.clone());
+        Some(entry)
+    }) else { return; };
+    let text = format!("[frame {}, {:.0}ms] {}: {}", entry.frame, entry.time,
+    entry.element.as_deref().unwrap_or("engine"), entry.message);
+    #[cfg(target_arch = "wasm32")]
+    {
+        let text = JsValue::from_str(&text);
+        match level {
+            LogLevel::Debug => web_sys::console::debug_1(&text),
+            LogLevel::Info => web_sys::console::log_1(&text),
+            LogLevel::Warn => web_sys::console::warn_1(&text),
+            LogLevel::Error => web_sys::console::error_1(&text),
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    eprintln!("{} {}", level.name(), text);
+}
+
+/// Runs `f` with an empty log, which has the
+/// same configuration, and restores the\
+/// log afterwards, so the entries logged in
+/// `f` won't mix with the game's entries.
+pub fn isolated<T>(f: impl FnOnce() -> T) -> T {
+    let outer = LOG.with_borrow_mut(|log| std::mem::replace(log, Logger {
+        entries: VecDeque::new(),
+        capacity: log.capacity,
+        min_level: log.min_level,
+        frame: 0,
+        time: 0_f64,
+    }));
+    let result = f();
+    LOG.set(outer);
+    result
+}
+
+/// Returns the latest entries in the log
+/// (up to `count`), from the oldest one.
+pub fn recent(count: usize) -> rhai::Array {
+    LOG.with_borrow(|log| log.entries.iter().skip(log.entries.len().saturating_sub(count))
+    .map(|entry| Dynamic::from_map(entry.to_map())).collect())
+}
+
+/// Returns the entries in the log as a
+/// JSON array, so the host page can show\
+/// them (from the oldest one).
+#[wasm_bindgen(js_name=logEntries)]
+pub fn log_entries() -> String {
+    let entries: Vec<String> = LOG.with_borrow(|log| log.entries.iter()
+    .map(|entry| rhai::format_map_as_json(&entry.to_map())).collect());
+    format!("[{}]", entries.join(","))
+}
+
+/// Removes every entry from the log.
+#[wasm_bindgen(js_name=logClear)]
+pub fn log_clear() {
+    LOG.with_borrow_mut(|log| log.entries.clear());
+}
+
+/// Sets the log's minimum level ("debug",
+/// "info", "warn" or "error").
+#[wasm_bindgen(js_name=logSetLevel)]
+pub fn log_set_level(level: &str) -> Result<(), JsValue> {
+    let level = LogLevel::from_name(level)
+    .ok_or_else(|| JsValue::from_str(&format!("Unknown log level '{}'.", level)))?;
+    LOG.with_borrow_mut(|log| log.min_level = level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<String> {
+        recent(usize::MAX).into_iter().map(|entry| entry.cast::<Map>()["message"].to_string()).collect()
+    }
+
+    #[test]
+    fn drops_entries_below_the_level_and_over_the_capacity() {
+        isolated(|| {
+            let mut config = Map::new();
+            config.insert("log-level".into(), "info".into());
+            config.insert("log-capacity".into(), (2 as rhai::INT).into());
+            configure(&config);
+            log(LogLevel::Debug, "dropped");
+            for message in ["first", "second", "third"] {
+                log(LogLevel::Info, message);
+            }
+            assert_eq!(messages(), ["second", "third"]);
+        });
+    }
+
+    #[test]
+    fn tags_entries_with_the_frame() {
+        isolated(|| {
+            advance_frame(16.0);
+            advance_frame(16.0);
+            log(LogLevel::Warn, "late");
+            let entry = recent(1)[0].clone().cast::<Map>();
+            assert_eq!((entry["frame"].as_int(), entry["time"].as_float()), (Ok(2), Ok(32.0)));
+            assert_eq!((entry["level"].to_string(), entry["element"].is_unit()), (String::from("warn"), true));
+        });
+    }
+
+    #[test]
+    fn isolated_logs_are_restored() {
+        log_clear();
+        log(LogLevel::Error, "outer");
+        isolated(|| log(LogLevel::Error, "inner"));
+        assert_eq!(messages().last().map(String::as_str), Some("outer"));
+    }
+}
@@ -0,0 +1,231 @@
+use std::{cell::{Cell, RefCell}, collections::{HashMap, HashSet}};
+
+use wasm_bindgen::prelude::*;
+use rhai::{Map, Dynamic};
+
+use crate::game::TableRow;
+
+/// How much a new sample moves the rolling
+/// average of a profiled section (the rest\
+/// of the weight stays on the older samples).
+const AVERAGE_WEIGHT: f64 = 0.1;
+
+/// The timings of a single profiled section
+/// (an element's callback, or a part of the\
+/// engine), in milliseconds.
+#[derive(Clone, Copy, Default)]
+struct Timings {
+    calls: u32,
+    last: f64,
+    average: f64,
+    worst: f64,
+}
+
+thread_local! {
+    /// Whether the sections are timed. It's off
+    /// by default, so the game doesn't pay for\
+    /// the timer when no one reads the profile.
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    /// The timings of every profiled section, by
+    /// the element which owns it (`None` for\
+    /// the engine itself) and the section's name.
+    static PROFILE: RefCell<HashMap<(Option<TableRow>, &'static str), Timings>> = RefCell::new(HashMap::new());
+    /// The names of the sections, which are
+    /// interned, so a section's key is only\
+    /// allocated the first time it's timed.
+    static SECTIONS: RefCell<HashSet<&'static str>> = RefCell::new(HashSet::new());
+}
+
+/// Enables the profiler if the state
+/// manager's config has a 'profile'\
+/// attribute which is set to `true`.
+pub fn configure(config: &Map) {
+    ENABLED.set(config.get("profile").is_some_and(|profile| profile.as_bool()
+    .expect("The state manager's config 'profile' attribute should be a boolean.")));
+}
+
+/// Returns the interned name of a section.
+///
+/// The names are leaked, but there are only
+/// as many of them as the sections (the\
+/// callbacks' names and the engine's parts).
+fn intern(section: &str) -> &'static str {
+    SECTIONS.with_borrow_mut(|sections| match sections.get(section) {
+        Some(&interned) => interned,
+        None => {
+            let interned: &'static str = Box::leak(Box::from(section));
+            sections.insert(interned);
+            interned
+        },
+    })
+}
+
+/// Returns the current time in milliseconds,
+/// using the high resolution timer if it's\
+/// available.
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> f64 {
+    web_sys::window().and_then(|window| window.performance())
+    .map_or_else(js_sys::Date::now, |performance| performance.now())
+}
+
+/// Returns the time since the first call in
+/// milliseconds, when the game runs headless.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> f64 {
+    thread_local! {
+        static START: std::time::Instant = std::time::Instant::now();
+    }
+    START.with(|start| start.elapsed().as_secs_f64() * 1000_f64)
+}
+
+/// Adds a sample to the timings of a section.
+pub fn record(element: Option<TableRow>, section: &str, elapsed: f64) {
+    PROFILE.with_borrow_mut(|profile| {
+        let timings = profile.entry((element, intern(section))).or_default();
+        timings.average = if timings.calls == 0 { elapsed }
+        else { timings.average + (elapsed - timings.average) * AVERAGE_WEIGHT };
+        timings.calls += 1;
+        timings.last = elapsed;
+        timings.worst = timings.worst.max(elapsed);
+    });
+}
+
+/// Runs `f`, and adds the time it took
+/// to the timings of the given section.
+///
+/// The time includes every nested call
+/// (like a message sent to another element).\
+/// If the profiler isn't enabled, `f` just runs.
+pub fn measure<T>(element: Option<TableRow>, section: &str, f: impl FnOnce() -> T) -> T {
+    if !ENABLED.get() { return f(); }
+    let start = now();
+    let result = f();
+    record(element, section, now() - start);
+    result
+}
+
+/// Runs `f` with an empty profile, and
+/// restores the profile afterwards, so\
+/// the sections timed in `f` won't mix
+/// with the game's timings.
+pub fn isolated<T>(f: impl FnOnce() -> T) -> T {
+    let outer = PROFILE.take();
+    let result = f();
+    PROFILE.set(outer);
+    result
+}
+
+/// Returns the timings of every profiled
+/// section, from the slowest one on average.
+pub fn report() -> rhai::Array {
+    let mut entries: Vec<((Option<TableRow>, &'static str), Timings)> = PROFILE.with_borrow(|profile|
+    profile.iter().map(|(key, timings)| (*key, *timings)).collect());
+    entries.sort_by(|(_, a), (_, b)| b.average.total_cmp(&a.average));
+    entries.into_iter().map(|((element, section), timings)| {
+        let mut map = Map::new();
+        map.insert("element".into(), element.map_or(String::from("engine"), |row| row.describe()).into());
+        map.insert("section".into(), section.into());
+        map.insert("calls".into(), (timings.calls as rhai::INT).into());
+        map.insert("last".into(), (timings.last as rhai::FLOAT).into());
+        map.insert("average".into(), (timings.average as rhai::FLOAT).into());
+        map.insert("worst".into(), (timings.worst as rhai::FLOAT).into());
+        Dynamic::from_map(map)
+    }).collect()
+}
+
+/// Formats the timings of every profiled section
+/// as a table, which can be printed as is.
+pub fn text_report() -> String {
+    let mut text = format!("{:<40} {:<24} {:>8} {:>10} {:>10}\n", "element", "section", "calls", "avg (ms)", "worst (ms)");
+    for entry in report() {
+        let entry = entry.cast::<Map>();
+        let field = |name: &str| entry[name].to_string();
+        text.push_str(&format!("{:<40} {:<24} {:>8} {:>10.3} {:>10.3}\n", field("element"), field("section"),
+        field("calls"), entry["average"].as_float().unwrap_or_default(), entry["worst"].as_float().unwrap_or_default()));
+    }
+    text
+}
+
+/// Returns the timings of every profiled
+/// section as a JSON array, so the host\
+/// page can show them.
+#[wasm_bindgen(js_name=profileReport)]
+pub fn profile_report() -> String {
+    let entries: Vec<String> = report().into_iter()
+    .map(|entry| rhai::format_map_as_json(&entry.cast::<Map>())).collect();
+    format!("[{}]", entries.join(","))
+}
+
+/// Returns the timings of every profiled
+/// section as a table (see `text_report`).
+#[wasm_bindgen(js_name=profileTextReport)]
+pub fn profile_text_report() -> String {
+    text_report()
+}
+
+/// Enables or disables the profiler, so
+/// the host page can turn it on while\
+/// the game runs.
+#[wasm_bindgen(js_name=profileEnable)]
+pub fn profile_enable(enabled: bool) {
+    ENABLED.set(enabled);
+}
+
+/// Removes every timing from the profile.
+#[wasm_bindgen(js_name=profileReset)]
+pub fn profile_reset() {
+    PROFILE.with_borrow_mut(|profile| profile.clear());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_measures_when_enabled() {
+        profile_reset();
+        profile_enable(false);
+        assert_eq!(measure(None, "disabled", || 1), 1);
+        assert!(report().is_empty());
+        profile_enable(true);
+        assert_eq!(measure(None, "enabled", || 2), 2);
+        let report = report();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].clone().cast::<Map>()["section"].to_string(), "enabled");
+        profile_enable(false);
+    }
+
+    #[test]
+    fn is_enabled_by_the_config() {
+        let mut config = Map::new();
+        configure(&config);
+        assert!(!ENABLED.get());
+        config.insert("profile".into(), true.into());
+        configure(&config);
+        assert!(ENABLED.get());
+        profile_enable(false);
+    }
+
+    #[test]
+    fn interns_the_sections() {
+        let name = String::from("update");
+        assert!(std::ptr::eq(intern(&name), intern("update")));
+    }
+
+    #[test]
+    fn keeps_a_rolling_average() {
+        profile_reset();
+        record(None, "section", 10.0);
+        record(None, "section", 20.0);
+        record(None, "other", 1.0);
+        let report = report();
+        // The slowest section on average comes first.
+        let section = report[0].clone().cast::<Map>();
+        assert_eq!(section["section"].to_string(), "section");
+        assert_eq!(section["calls"].as_int().unwrap(), 2);
+        assert_eq!(section["average"].as_float().unwrap(), 11.0);
+        assert_eq!(section["worst"].as_float().unwrap(), 20.0);
+        assert!(text_report().lines().nth(1).unwrap().starts_with("engine"));
+    }
+}
@@ -0,0 +1,180 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use rhai::{Dynamic, EvalAltResult};
+
+use super::element;
+
+/// A small pseudo random number generator
+/// (SplitMix64), which always produces the\
+/// same numbers for the same seed, on every
+/// browser and platform.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+    /// Returns a float in the range [0, 1).
+    pub fn float(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+    /// Returns an integer in the range
+    /// [0, bound) (`bound` can't be 0).
+    pub fn below(&mut self, bound: u64) -> u64 {
+        // Multiplying instead of using the remainder
+        // keeps the numbers evenly distributed\
+        // (close enough for any bound a script uses).
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+    /// Returns an integer in the
+    /// range [min, max] (inclusive).
+    pub fn int(&mut self, min: i64, max: i64) -> i64 {
+        min + self.below((max - min) as u64 + 1) as i64
+    }
+}
+
+/// The random number streams of a game.
+///
+/// Every named stream has it's own generator,
+/// which is seeded by the game's seed and the\
+/// stream's name, so using one stream doesn't
+/// change the numbers another one produces.
+#[derive(Clone, Debug)]
+pub struct RandomStreams {
+    seed: u32,
+    main: Rng,
+    named: HashMap<String, Rng>,
+}
+
+impl RandomStreams {
+    pub fn new(seed: u32) -> Self {
+        Self { seed, main: Rng::new(seed as u64), named: HashMap::new() }
+    }
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+    /// Returns the generator of a stream (the
+    /// main one if `name` is `None`), which is\
+    /// created the first time it's used.
+    pub fn stream(&mut self, name: Option<&str>) -> &mut Rng {
+        let Some(name) = name else { return &mut self.main; };
+        let seed = self.seed;
+        self.named.entry(String::from(name)).or_insert_with(|| {
+            // The FNV-1a hash of the name is
+            // mixed into the game's seed.
+            let hash = name.bytes().fold(0xCBF29CE484222325_u64,
+            |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001B3));
+            Rng::new(Rng::new(seed as u64 ^ hash).next_u64())
+        })
+    }
+}
+
+/// Runs `f` with a random number stream of
+/// the game, whose properties are given\
+/// (the state manager's ones).
+fn with_rng<T>(game_props: &Rc<RefCell<Dynamic>>, stream: Option<&str>,
+f: impl FnOnce(&mut Rng) -> T) -> Result<T, Box<EvalAltResult>> {
+    let Ok(mut borrow) = game_props.try_borrow_mut() else {
+        return Err("Can't use the random number functions while the 'Game' properties are in use.".into());
+    };
+    let mut game = borrow.write_lock::<element::Game>().expect("write_lock cast should succeed");
+    Ok(f(game.random.stream(stream)))
+}
+
+/// Returns an integer in the
+/// range [min, max] (inclusive).
+pub fn rand_int(game_props: &Rc<RefCell<Dynamic>>, stream: Option<&str>,
+min: rhai::INT, max: rhai::INT) -> Result<rhai::INT, Box<EvalAltResult>> {
+    if min > max {
+        return Err(format!("Tried to use 'rand_int' with a minimum ({}) bigger than the maximum ({}).", min, max).into());
+    }
+    with_rng(game_props, stream, |rng| rng.int(min as i64, max as i64) as rhai::INT)
+}
+
+/// Returns a float in the range [0, 1).
+pub fn rand_float(game_props: &Rc<RefCell<Dynamic>>, stream: Option<&str>)
+-> Result<rhai::FLOAT, Box<EvalAltResult>> {
+    // A float which is too close to 1 to be
+    // told apart from it is rounded down.
+    with_rng(game_props, stream, |rng| (rng.float() as rhai::FLOAT).min(1.0 - rhai::FLOAT::EPSILON / 2.0))
+}
+
+/// Returns a random member of an array.
+pub fn rand_choice(game_props: &Rc<RefCell<Dynamic>>, stream: Option<&str>,
+array: rhai::Array) -> Result<Dynamic, Box<EvalAltResult>> {
+    if array.is_empty() {
+        return Err("Tried to use 'rand_choice' with an empty array.".into());
+    }
+    let idx = with_rng(game_props, stream, |rng| rng.below(array.len() as u64) as usize)?;
+    Ok(array[idx].clone())
+}
+
+/// Shuffles an array in place
+/// (a Fisher-Yates shuffle).
+pub fn rand_shuffle(game_props: &Rc<RefCell<Dynamic>>, stream: Option<&str>,
+array: &mut rhai::Array) -> Result<(), Box<EvalAltResult>> {
+    with_rng(game_props, stream, |rng| {
+        for i in (1..array.len()).rev() {
+            array.swap(i, rng.below(i as u64 + 1) as usize);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        let (mut a, mut b) = (Rng::new(42), Rng::new(43));
+        assert!((0..10).any(|_| a.next_u64() != b.next_u64()));
+    }
+
+    #[test]
+    fn numbers_stay_in_range() {
+        let mut rng = Rng::new(7);
+        let (mut saw_min, mut saw_max) = (false, false);
+        for _ in 0..10_000 {
+            let float = rng.float();
+            assert!((0.0..1.0).contains(&float));
+            assert!(rng.below(3) < 3);
+            let int = rng.int(-2, 2);
+            assert!((-2..=2).contains(&int));
+            saw_min |= int == -2;
+            saw_max |= int == 2;
+        }
+        assert!(saw_min && saw_max);
+        assert_eq!(rng.int(5, 5), 5);
+        let int = rng.int(i32::MIN as i64, i32::MAX as i64);
+        assert!((i32::MIN as i64..=i32::MAX as i64).contains(&int));
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut streams = RandomStreams::new(42);
+        let main: Vec<u64> = (0..5).map(|_| streams.stream(None).next_u64()).collect();
+        let mut other = RandomStreams::new(42);
+        other.stream(Some("loot")).next_u64();
+        let other_main: Vec<u64> = (0..5).map(|_| other.stream(None).next_u64()).collect();
+        assert_eq!(main, other_main);
+        let mut a = RandomStreams::new(42);
+        let mut b = RandomStreams::new(42);
+        assert_eq!(a.stream(Some("loot")).next_u64(), b.stream(Some("loot")).next_u64());
+        assert_ne!(a.stream(Some("enemies")).next_u64(), b.stream(Some("loot")).next_u64());
+    }
+}
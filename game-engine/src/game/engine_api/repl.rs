@@ -0,0 +1,73 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::prelude::*;
+use rhai::Engine;
+
+use super::{GameElementSet, MessageTarget};
+
+thread_local! {
+    /// The engine and the elements of the running
+    /// game, which the console evaluates code with.
+    static CONSOLE: RefCell<Option<(Rc<Engine>, Rc<GameElementSet>)>> = const { RefCell::new(None) };
+}
+
+/// Lets the console evaluate code with
+/// the given engine and game elements.\
+/// Called once the game (or the headless
+/// game) is created.
+pub fn install(engine: &Rc<Engine>, game_elements: &Rc<GameElementSet>) {
+    CONSOLE.set(Some((Rc::clone(engine), Rc::clone(game_elements))));
+}
+
+/// Evaluates a piece of code in the context
+/// of an element of the running game, and\
+/// returns a description of the result, or
+/// throws the error as a string.
+///
+/// The target is "state-manager", "scene"
+/// or "object" (the object instance at the\
+/// given index of the object stack).
+#[wasm_bindgen(js_name=consoleEval)]
+pub fn console_eval(target: &str, index: u32, code: &str) -> Result<String, JsValue> {
+    Ok(eval(target, index, code)?)
+}
+
+/// Evaluates a piece of code the same
+/// way `console_eval` does, for callers\
+/// which aren't the host page (like the
+/// headless runner's console).
+pub fn eval(target: &str, index: u32, code: &str) -> Result<String, String> {
+    let target = match target {
+        "state-manager" => MessageTarget::StateManager,
+        "scene" => MessageTarget::CurScene,
+        "object" => MessageTarget::Object(index),
+        _ => { return Err(format!("Unknown console target '{}'.", target)); },
+    };
+    // Clone the counted references, so the
+    // console won't stay borrowed while the
+    // code runs.
+    let Some((engine, game_elements)) = CONSOLE.with_borrow(|console| console.clone()) else {
+        return Err(String::from("The game isn't running yet."));
+    };
+    game_elements.eval(&engine, target, code)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::game::HeadlessGame;
+
+    #[test]
+    fn evaluates_in_the_targets_scope() {
+        let _game = HeadlessGame::for_test("let lives = 3;", &[
+            ("scene", 2, &crate::game::TEST_SCENE_CONFIG.replace("[]", concat!(r##"[{"id": 2, "layer": 0, "x": 4, "y": 0, "##,
+            r##""scale-x": 1, "scale-y": 1, "color": "#FFFFFF", "alpha": 255}]"##)), "let level = \"start\";"),
+            ("object", 1, r#"{"sprites": []}"#, ""),
+        ]);
+        assert_eq!(eval("state-manager", 0, "lives"), Ok(String::from("3")));
+        assert_eq!(eval("scene", 0, "level"), Ok(String::from("\"start\"")));
+        assert_eq!(eval("object", 0, "Object.position.x"), Ok(String::from("4.0")));
+        assert!(eval("object", 1, "1").is_err());
+        assert!(eval("camera", 0, "1").is_err_and(|err| err.contains("Unknown console target")));
+    }
+}
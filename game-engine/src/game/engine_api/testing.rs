@@ -0,0 +1,358 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use wasm_bindgen::prelude::*;
+use rhai::{Engine, Map, Dynamic, EvalAltResult, NativeCallContext};
+
+use crate::{data, game::TableRow};
+
+use super::{element, debugger, logger, profiler, random::RandomStreams, create_api, ElementDefinition, ElementDefinitions,
+    ElementResources, GameElementSet, KeyState, KeyStates, LibraryResolver, RUNNING_ELEMENT};
+
+/// The components of the game a test
+/// runs in, which the test helpers use.
+#[derive(Clone)]
+struct TestGame {
+    elements: Rc<GameElementSet>,
+    element_defs: Rc<RefCell<ElementDefinitions>>,
+    key_states: Rc<RefCell<KeyStates>>,
+}
+
+thread_local! {
+    /// The game of the running test, or
+    /// `None` if no test is running.
+    static TEST_GAME: RefCell<Option<TestGame>> = const { RefCell::new(None) };
+}
+
+/// The outcome of a single test function.
+pub struct TestResult {
+    pub element: String,
+    pub test: String,
+    pub error: Option<String>,
+}
+
+impl TestResult {
+    /// Converts the result into a rhai map,
+    /// so it can be formatted as JSON.
+    fn to_map(&self) -> Map {
+        let mut map = Map::new();
+        map.insert("element".into(), self.element.clone().into());
+        map.insert("test".into(), self.test.clone().into());
+        map.insert("passed".into(), self.error.is_none().into());
+        map.insert("error".into(), self.error.clone().map_or(Dynamic::UNIT, Dynamic::from));
+        map
+    }
+}
+
+/// Returns the game of the running test,
+/// or an error naming the helper which\
+/// was called outside of a test.
+fn test_game(helper: &str) -> Result<TestGame, Box<EvalAltResult>> {
+    TEST_GAME.with_borrow(|game| game.clone()).ok_or_else(||
+    format!("The '{}' function can only be used by a test, while the tests are running.", helper).into())
+}
+
+/// Runs a single frame of the test's game,
+/// the same way the main loop does.
+///
+/// The test's game loaded every element when
+/// it was created, and the elements which\
+/// were changed since are the running game's
+/// to reload, so nothing is reloaded here.
+fn step(game: &TestGame, engine: &Engine, elapsed: f64) -> Result<(), String> {
+    game.elements.run_frame(engine, &game.element_defs, elapsed, || {
+        // Every key was pressed or released
+        // at least a frame ago now.
+        for state in game.key_states.borrow_mut().values_mut() {
+            state.just_pressed = false;
+            state.just_released = false;
+        }
+    }, |_| Vec::new())
+}
+
+/// Returns the length of a frame of the
+/// test's game, in milliseconds.
+fn frame_time(game: &TestGame) -> f64 {
+    1000_f64 / game.elements.state_manager.properties.borrow()
+    .read_lock::<element::Game>().expect("read_lock cast should succeed").fps.max(1) as f64
+}
+
+/// Sets a key's state, as if it was
+/// pressed or released on the keyboard.
+fn set_key(helper: &str, key: &str, held: bool) -> Result<(), Box<EvalAltResult>> {
+    let game = test_game(helper)?;
+    let mut key_states = game.key_states.borrow_mut();
+    let state = key_states.entry(String::from(key))
+    .or_insert(KeyState { is_held: false, just_pressed: false, just_released: false });
+    if state.is_held != held {
+        state.is_held = held;
+        state.just_pressed = held;
+        state.just_released = !held;
+    }
+    Ok(())
+}
+
+/// Registers the functions the tests use
+/// to drive the game and check it's state.
+///
+/// They are registered on every engine, but
+/// they raise an error unless a test runs.
+pub fn register(engine: &mut Engine) {
+    engine.register_fn("tick", |context: NativeCallContext, frames: rhai::INT| -> Result<(), Box<EvalAltResult>> {
+        let game = test_game("tick")?;
+        for _ in 0..frames.max(0) {
+            step(&game, context.engine(), frame_time(&game))?;
+        }
+        Ok(())
+    });
+    // Frames keep their usual length, so
+    // the game advances by the given time\
+    // (rounded up to a whole frame).
+    engine.register_fn("advance_time", |context: NativeCallContext, time: rhai::FLOAT| -> Result<(), Box<EvalAltResult>> {
+        let game = test_game("advance_time")?;
+        let frame_time = frame_time(&game);
+        for _ in 0..(time.max(0.0) as f64 / frame_time).ceil() as u32 {
+            step(&game, context.engine(), frame_time)?;
+        }
+        Ok(())
+    });
+    engine.register_fn("press_key", |key: &str| set_key("press_key", key, true))
+          .register_fn("release_key", |key: &str| set_key("release_key", key, false));
+    engine.register_fn("load_scene", |context: NativeCallContext, id: rhai::INT| -> Result<(), Box<EvalAltResult>> {
+        let game = test_game("load_scene")?;
+        game.elements.state_manager.properties.borrow_mut()
+        .write_lock::<element::Game>().expect("write_lock cast should succeed").set_cur_scene(id)?;
+        game.elements.follow_cur_scene(context.engine(), &game.element_defs.borrow())?;
+        Ok(())
+    });
+    engine.register_fn("assert", |condition: bool| -> Result<(), Box<EvalAltResult>> {
+        if condition { Ok(()) } else { Err("Assertion failed.".into()) }
+    });
+    engine.register_fn("assert", |condition: bool, message: &str| -> Result<(), Box<EvalAltResult>> {
+        if condition { Ok(()) } else { Err(format!("Assertion failed: {}", message).into()) }
+    });
+    // The values are compared with the '=='
+    // operator if it's defined for them, and\
+    // by their descriptions otherwise (like
+    // the values of the API types).
+    engine.register_fn("assert_eq", |context: NativeCallContext, actual: Dynamic, expected: Dynamic|
+    -> Result<(), Box<EvalAltResult>> {
+        let equal = context.call_native_fn::<bool>("==", (actual.clone(), expected.clone()))
+        .unwrap_or_else(|_| debugger::inspect(&actual) == debugger::inspect(&expected));
+        if equal { Ok(()) } else {
+            Err(format!("Assertion failed: expected {}, got {}.",
+            debugger::inspect(&expected), debugger::inspect(&actual)).into())
+        }
+    });
+}
+
+/// Loads every element of the game, which isn't
+/// already loaded, so the test's game can switch\
+/// to any scene (the running game only loads the
+/// elements which are in the `elementsToLoad` list).
+fn load_all_elements(engine: &Engine, libraries: &LibraryResolver, element_defs: &mut ElementDefinitions) {
+    for (int_id, int_type) in data::all_elements() {
+        if element_defs.contains_key(&int_id) { continue; }
+        element_defs.insert(int_id, ElementDefinition::new(engine, libraries, TableRow::Element(int_id, int_type)));
+    }
+}
+
+/// Runs `f` apart from the running game, with
+/// it's own log and profile, and no running\
+/// element, which are all restored afterwards,
+/// so a test won't leave any trace in the game.
+fn isolated<T>(f: impl FnOnce() -> T) -> T {
+    let running_element = RUNNING_ELEMENT.take();
+    let result = logger::isolated(|| profiler::isolated(f));
+    RUNNING_ELEMENT.set(running_element);
+    result
+}
+
+/// Runs a single test function of a test
+/// element in a fresh copy of the game.
+fn run_test(test_id: u32, name: &str) -> Result<(), String> {
+    let element_defs: Rc<RefCell<ElementDefinitions>> = Rc::new(RefCell::new(HashMap::new()));
+    let libraries = LibraryResolver::new();
+    let (engine, elements, key_states) = create_api(&element_defs, &libraries)?;
+    // Every test starts with the same seed, whatever
+    // the game's seed is, so it's outcome is stable.
+    elements.state_manager.properties.borrow_mut().write_lock::<element::Game>()
+    .expect("write_lock cast should succeed").random = RandomStreams::new(element::DEFAULT_SEED);
+    load_all_elements(&engine, &libraries, &mut element_defs.borrow_mut());
+    elements.call_fn_on_all("init", (), &engine)?;
+    let definition = Rc::clone(element_defs.borrow().get(&test_id)
+    .expect("The test element should be loaded with every other element.").as_ref()?);
+    // The test gets it's own resources, so
+    // it's scope won't leak into other tests.
+    let mut resources = ElementResources::new(definition);
+    TEST_GAME.set(Some(TestGame { elements, element_defs, key_states }));
+    let result = resources.run_script(&engine)
+    .and_then(|_| resources.call_fn(&engine, name, ()).map(|_| ()));
+    TEST_GAME.set(None);
+    result
+}
+
+/// Runs every test function (a function named
+/// `test_*`, which has no parameters) of every\
+/// test element in the game, each in a fresh copy
+/// of the game, and returns their outcomes.
+///
+/// An error is reported with the element and the
+/// line it was raised on, like any script error.
+///
+/// The game's data is read through the `data`
+/// module, so the tests run in the browser\
+/// (through the `runTests` export), or against
+/// a project file with the headless runner.
+pub fn run_tests() -> Vec<TestResult> {
+    let mut results = Vec::new();
+    let engine = Engine::new_raw();
+    let libraries = LibraryResolver::new();
+    for (test_id, int_type) in data::all_elements() {
+        if int_type != 5 { continue; }
+        let row = TableRow::Element(test_id, 5);
+        // The test element is only compiled here to
+        // find it's tests, so a raw engine is enough.
+        let names: Vec<String> = match ElementDefinition::new(&engine, &libraries, row) {
+            Ok(definition) => definition.script.iter_functions()
+            .filter(|func| func.name.starts_with("test_") && func.params.is_empty())
+            .map(|func| String::from(func.name)).collect(),
+            Err(err) => {
+                results.push(TestResult { element: row.describe(), test: String::new(), error: Some(err) });
+                continue;
+            },
+        };
+        for name in names {
+            let error = isolated(|| run_test(test_id, &name)).err();
+            results.push(TestResult { element: row.describe(), test: name, error });
+        }
+    }
+    results
+}
+
+/// Formats the outcomes of the tests as
+/// a list, which can be printed as is.
+pub fn text_report(results: &[TestResult]) -> String {
+    let mut text = String::new();
+    for result in results {
+        match &result.error {
+            None => text.push_str(&format!("PASS {}: {}\n", result.element, result.test)),
+            Some(err) => text.push_str(&format!("FAIL {}: {}\n{}\n", result.element, result.test, err)),
+        }
+    }
+    let passed = results.iter().filter(|result| result.error.is_none()).count();
+    text.push_str(&format!("{} passed, {} failed\n", passed, results.len() - passed));
+    text
+}
+
+/// Runs the game's tests and returns their
+/// outcomes as a JSON array, so the host\
+/// page (or a headless browser) can show them.
+#[wasm_bindgen(js_name=runTests)]
+pub fn run_tests_json() -> String {
+    let entries: Vec<String> = run_tests().iter()
+    .map(|result| rhai::format_map_as_json(&result.to_map())).collect();
+    format!("[{}]", entries.join(","))
+}
+
+/// Runs the game's tests and returns their
+/// outcomes as a list (see `text_report`).
+#[wasm_bindgen(js_name=runTestsTextReport)]
+pub fn run_tests_text_report() -> String {
+    text_report(&run_tests())
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::game::{TEST_CONFIG, TEST_SCENE_CONFIG};
+
+    /// Runs the tests of a test element, in a game
+    /// with the given state manager script and a\
+    /// single empty scene.
+    fn run(state_script: &str, test_script: &str) -> Vec<TestResult> {
+        data::use_test_project(TEST_CONFIG, state_script, &[
+            ("scene", 2, TEST_SCENE_CONFIG, ""),
+            ("tests", 5, r#"{"libraries": []}"#, test_script),
+        ]);
+        run_tests()
+    }
+
+    fn outcomes(results: &[TestResult]) -> Vec<(&str, bool)> {
+        results.iter().map(|result| (result.test.as_str(), result.error.is_none())).collect()
+    }
+
+    #[test]
+    fn reports_passing_and_failing_tests() {
+        let results = run("", concat!(
+            "fn test_passes() { assert_eq(1 + 1, 2); }\n",
+            "fn test_fails() { assert_eq(Game.fps, 30); }\n",
+            "fn helper(x) { }\n",
+            "fn not_a_test() { assert(false); }\n"));
+        let mut outcomes = outcomes(&results);
+        outcomes.sort();
+        assert_eq!(outcomes, [("test_fails", false), ("test_passes", true)]);
+        let error = results.iter().find_map(|result| result.error.as_ref()).unwrap();
+        assert!(error.contains("expected 30, got 60"), "{}", error);
+        assert!(error.contains("line 2"), "{}", error);
+        assert!(text_report(&results).ends_with("1 passed, 1 failed\n"));
+    }
+
+    #[test]
+    fn reports_compile_errors() {
+        let results = run("", "fn test_broken( {");
+        assert_eq!(results.len(), 1);
+        assert!(results[0].test.is_empty() && results[0].error.is_some());
+    }
+
+    #[test]
+    fn ticks_run_the_update_phase() {
+        let results = run("State[\"frames\"] = 0;\nfn update(elapsed) { State[\"frames\"] += 1; }",
+        concat!(
+            "fn test_tick() { tick(3); assert_eq(State[\"frames\"], 3); }\n",
+            // 50 milliseconds are 3 frames at 60 fps (rounded up).
+            "fn test_advance_time() { advance_time(50.0); assert_eq(State[\"frames\"], 3); }\n"));
+        assert_eq!(outcomes(&results).iter().filter(|(_, passed)| *passed).count(), 2, "{}", text_report(&results));
+    }
+
+    #[test]
+    fn every_test_gets_a_fresh_game() {
+        let results = run("State[\"count\"] = 0;\nfn update(elapsed) { State[\"count\"] += 1; }", concat!(
+            "fn test_first() { tick(1); assert_eq(State[\"count\"], 1); }\n",
+            "fn test_second() { tick(1); assert_eq(State[\"count\"], 1); }\n"));
+        assert!(results.iter().all(|result| result.error.is_none()), "{}", text_report(&results));
+    }
+
+    #[test]
+    fn keys_are_pressed_for_a_single_frame() {
+        let results = run("", concat!(
+            "fn test_keys() {\n",
+            "  press_key(\"Space\");\n",
+            "  assert(key_just_pressed(\"Space\"));\n",
+            "  tick(1);\n",
+            "  assert(!key_just_pressed(\"Space\") && key_is_held(\"Space\"));\n",
+            "  release_key(\"Space\");\n",
+            "  assert(key_just_released(\"Space\") && !key_is_held(\"Space\"));\n",
+            "}\n"));
+        assert!(results[0].error.is_none(), "{}", text_report(&results));
+    }
+
+    #[test]
+    fn helpers_fail_outside_of_tests() {
+        assert!(test_game("tick").is_err_and(|err| err.to_string().contains("'tick'")));
+    }
+
+    #[test]
+    fn tests_leave_no_trace_in_the_running_game() {
+        logger::log_clear();
+        logger::log(logger::LogLevel::Info, "game");
+        profiler::record(None, "game", 1.0);
+        let results = run("", "fn test_logs() { log_info(\"test\"); tick(1); }");
+        assert!(results[0].error.is_none(), "{}", text_report(&results));
+        let log = logger::recent(usize::MAX);
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].clone().cast::<Map>()["message"].to_string(), "game");
+        assert_eq!(profiler::report().len(), 1);
+        assert!(RUNNING_ELEMENT.get().is_none());
+        assert!(TEST_GAME.with_borrow(|game| game.is_none()));
+    }
+}
@@ -1,5 +1,5 @@
 
-use std::{rc::Rc, collections::HashMap};
+use std::{rc::Rc, collections::{HashMap, HashSet}};
 
 use wasm_bindgen::{prelude::*, JsCast};
 use web_sys::{WebGlProgram, WebGlRenderingContext, WebGlShader, WebGlBuffer, WebGlTexture, WebGlContextAttributes};
@@ -17,7 +17,7 @@ static mut CANVAS_ID: &str = "canvas";
 const MAX_QUAD_COUNT: i32 = 1000;
 const INDCIES_PER_QUAD: i32 = 6;
 const VERTICES_PER_QUAD: i32 = 4;
-const FLOATS_PER_VERTEX: i32 = 13;
+const FLOATS_PER_VERTEX: i32 = 16;
 
 const VERTEX_SHADER: &str = r#"
 attribute vec2 a_position;
@@ -26,10 +26,13 @@ attribute vec2 a_texcoord;
 attribute vec2 a_texsize;
 attribute vec2 a_scale;
 attribute float a_texindex;
+attribute vec2 a_pivot;
+attribute float a_rotation;
 
 uniform vec2 u_resolution;
 uniform vec2 u_camera;
 uniform float u_zoom;
+uniform float u_cam_rotation;
 
 varying vec4 v_color;
 varying vec2 v_texcoord;
@@ -45,7 +48,21 @@ void main() {
     v_scale = a_scale;
     v_texindex = a_texindex;
 
-    vec2 camRelative = a_position - u_camera;
+    // Rotate the vertex around the pivot (the
+    // object's position, where the sprite's
+    // origin is). Because the y axis points
+    // down, a positive angle is clockwise.
+    float sinRot = sin(a_rotation);
+    float cosRot = cos(a_rotation);
+    vec2 local = a_position - a_pivot;
+    vec2 position = a_pivot + vec2(local.x * cosRot - local.y * sinRot, local.x * sinRot + local.y * cosRot);
+
+    // The world rotates the other way around
+    // the camera (see `Camera::world_to_screen`).
+    vec2 camOffset = position - u_camera;
+    float sinCam = sin(-u_cam_rotation);
+    float cosCam = cos(-u_cam_rotation);
+    vec2 camRelative = vec2(camOffset.x * cosCam - camOffset.y * sinCam, camOffset.x * sinCam + camOffset.y * cosCam);
     vec2 clipSpace = camRelative * abs(u_zoom) * 2.0 / u_resolution;
     gl_Position = vec4(clipSpace * vec2(1, -1), 0, 1);
 }
@@ -104,7 +121,7 @@ void main() {
 }
 "#;
 
-const ATTRIBUTE_MATRIX: [(&str, i32, i32); 6] = [
+const ATTRIBUTE_MATRIX: [(&str, i32, i32); 8] = [
     // name, size, offset
     ("a_position", 2, 0),
     ("a_color", 4, 8),
@@ -112,16 +129,82 @@ const ATTRIBUTE_MATRIX: [(&str, i32, i32); 6] = [
     ("a_texsize", 2, 32),
     ("a_scale", 2, 40),
     ("a_texindex", 1, 48),
+    ("a_pivot", 2, 52),
+    ("a_rotation", 1, 60),
 ];
 
-const UNIFORM_LIST: [&str; 5] = [
+const UNIFORM_LIST: [&str; 6] = [
     "u_resolution",
     "u_camera",
     "u_zoom",
+    "u_cam_rotation",
     "u_cam_color",
     "u_textures",
 ];
 
+/// An object instance in the order
+/// the instances of a view are drawn in,\
+/// with the index of it's layer's view
+/// (in `WebGlRenderer::layer_views`).
+#[derive(Clone, Copy)]
+struct DrawEntry {
+    view: u32,
+    order: u32,
+    index: u32,
+    key: f32,
+}
+
+/// The uniform values (and the blend mode)
+/// the quads of a layer are drawn with.
+#[derive(Clone, Copy, PartialEq)]
+struct LayerView {
+    position: [f32; 2],
+    zoom: f32,
+    rotation: f32,
+    color: [f32; 4],
+    blend_mode: element::BlendMode,
+}
+
+impl LayerView {
+    /// Creates the view of a layer, using the
+    /// camera's view and color, and the size\
+    /// of the viewport (in pixels).
+    ///
+    /// A screen space layer's view has it's top
+    /// left corner on the viewport's top left\
+    /// corner, and it skips the camera's tint.
+    fn new(layer: &element::Layer, cam_view: ([f32; 2], f32, f32), cam_color: [f32; 4],
+    resolution: [f32; 2]) -> Self {
+        let (position, zoom, rotation) = cam_view;
+        let tint = [from_0_225_to_0_1(layer.tint.r), from_0_225_to_0_1(layer.tint.g),
+        from_0_225_to_0_1(layer.tint.b), from_0_225_to_0_1(layer.tint.a) * layer.opacity];
+        if layer.screen_space {
+            return Self { position: [resolution[0] / 2.0, resolution[1] / 2.0], zoom: 1.0,
+            rotation: 0.0, color: tint, blend_mode: layer.blend_mode };
+        }
+        Self {
+            // The parallax scales the camera's position,
+            // which is floored again to keep the pixels\
+            // aligned.
+            position: [(position[0] * layer.parallax.x).floor(), (position[1] * layer.parallax.y).floor()],
+            zoom,
+            rotation,
+            color: [0, 1, 2, 3].map(|i| tint[i] * cam_color[i]),
+            blend_mode: layer.blend_mode,
+        }
+    }
+}
+
+/// The state of the frame which is being
+/// rendered, shared by every camera's view.
+struct FrameState<'a> {
+    game: &'a element::Game,
+    scene_props: &'a element::Scene,
+    object_stack: &'a [engine_api::ElementHandler],
+    asset_defs: &'a AssetDefinitions,
+    elapsed: f64,
+}
+
 /// This enum will help
 /// the `AssetDefinition`\
 /// struct to store data of
@@ -173,7 +256,7 @@ impl AssetData {
             WebGlRenderingContext::UNSIGNED_BYTE,
             Some(image::load_from_memory_with_format(&image_data,
             image::ImageFormat::Png).expect("Couldn't load PNG file.")
-            .to_rgba8().pixels().map(|pixel| { pixel.0 }).flatten()
+            .to_rgba8().pixels().flat_map(|pixel| { pixel.0 })
             .collect::<Vec<u8>>().as_slice())
         )?;
         // Return the image data with the webgl texture object.
@@ -186,7 +269,6 @@ impl AssetData {
 /// for a single asset defined in the
 /// project file/game data file.
 pub struct AssetDefinition {
-    pub row: TableRow,
     pub asset_data: AssetData,
     pub config: rhai::Map,
 }
@@ -225,7 +307,7 @@ impl AssetDefinition {
             return Err(JsValue::from_str(&row.to_err_string(&err.to_string())));
         }
         // Return the asset definition.
-        Ok(Self{row,
+        Ok(Self{
         asset_data: asset_data.expect(
             concat!("This Err should",
             " have been caught by this",
@@ -252,6 +334,13 @@ pub struct WebGlRenderer {
     vertex_vec: Vec<f32>,
     texture_slots: Vec<u32>,
     max_texture_units: i32,
+    // Reused every time a view is rendered,
+    // so sorting the layers won't allocate.
+    draw_order: Vec<DrawEntry>,
+    layer_views: Vec<LayerView>,
+    // The indices of the object instances
+    // which were animated this frame.
+    animated: HashSet<u32>,
 }
 
 impl WebGlRenderer {
@@ -322,7 +411,7 @@ impl WebGlRenderer {
             // the indcies which represent 
             // the order of the vertices'rendering.
             indcies.extend_from_slice(&[
-                (0 + VERTICES_PER_QUAD * i) as u16,
+                (VERTICES_PER_QUAD * i) as u16,
                 (1 + VERTICES_PER_QUAD * i) as u16,
                 (2 + VERTICES_PER_QUAD * i) as u16,
                 (2 + VERTICES_PER_QUAD * i) as u16,
@@ -387,7 +476,8 @@ impl WebGlRenderer {
 
         // Return the webgl renderer.
         Ok(Self{gl_context, gl_program, uniform_locations, vertex_buffer, index_buffer,
-        vertex_vec, texture_slots, max_texture_units})
+        vertex_vec, texture_slots, max_texture_units, draw_order: Vec::new(), layer_views: Vec::new(),
+        animated: HashSet::new()})
     }
 
     /// This function will render the
@@ -407,29 +497,6 @@ impl WebGlRenderer {
         self.gl_context.clear_color(from_0_225_to_0_1(game.clear_red),
         from_0_225_to_0_1(game.clear_green), from_0_225_to_0_1(game.clear_blue), 1.0);
 
-        // Clear the canvas
-        self.gl_context.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
-        // Set the blending method the alpha of the images will control
-        self.gl_context.enable(WebGlRenderingContext::BLEND);
-        self.gl_context.blend_func(WebGlRenderingContext::SRC_ALPHA, WebGlRenderingContext::ONE_MINUS_SRC_ALPHA);
-
-        // Set the uniform values
-        if let Some(location) = self.uniform_locations.get("u_camera") {
-            self.gl_context.uniform2f(Some(location),
-            scene_props.camera.position.x.floor(), scene_props.camera.position.y.floor());
-        } else { return Err("Couldn't find uniform 'u_camera'".into()); }
-        if let Some(location) = self.uniform_locations.get("u_zoom") {
-            self.gl_context.uniform1f(Some(location), scene_props.camera.zoom);
-        } else { return Err("Couldn't find uniform 'u_zoom'".into()); }
-        if let Some(location) = self.uniform_locations.get("u_cam_color") {
-            self.gl_context.uniform4f(Some(location), from_0_225_to_0_1(scene_props.camera.color.r),
-            from_0_225_to_0_1(scene_props.camera.color.g),from_0_225_to_0_1(scene_props.camera.color.b),
-            from_0_225_to_0_1(scene_props.camera.color.a));
-        } else { return Err("Couldn't find uniform 'u_cam_color'".into()); }
-        if let Some(location) = self.uniform_locations.get("u_resolution") {
-            self.gl_context.uniform2f(Some(location), game.canvas_width as f32, game.canvas_height as f32);
-        } else { return Err("Couldn't find uniform 'u_resolution'".into()); }
-
         // resize the canvas if needed
         {
             // Convert the canvas element into an HTMLCanvasElement object.
@@ -449,9 +516,56 @@ impl WebGlRenderer {
             canvas.set_attribute("height", &format!("{}", game.canvas_height))?;
         } // `canvas`, `device_pixel_ratio`, `canvas_css_width` and `canvas_css_height` drop here.
 
-        // Inform the webgl context of the canvas resize,
-        // so that it can render the scene at the correct size.
+        // Clear the whole canvas, before the
+        // cameras render to their viewports.
         self.gl_context.viewport(0, 0, game.canvas_width as i32, game.canvas_height as i32);
+        self.gl_context.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
+        // Set the blending method the alpha of the images will control
+        self.gl_context.enable(WebGlRenderingContext::BLEND);
+        self.gl_context.blend_func(WebGlRenderingContext::SRC_ALPHA, WebGlRenderingContext::ONE_MINUS_SRC_ALPHA);
+
+        // Render the scene through every camera,
+        // in it's own area of the canvas (the\
+        // scissor test keeps the quads which are
+        // outside of the area from showing up).
+        self.gl_context.enable(WebGlRenderingContext::SCISSOR_TEST);
+        self.animated.clear();
+        let frame = FrameState { game, scene_props, object_stack, asset_defs, elapsed };
+        for camera in &scene_props.cameras {
+            let [x, y, width, height] = camera.pixel_viewport(game);
+            if width <= 0.0 || height <= 0.0 { continue; }
+            // The y axis of WebGL's viewports
+            // points up, unlike the canvas'.
+            let gl_y = game.canvas_height - (y + height);
+            self.gl_context.viewport(x as i32, gl_y as i32, width as i32, height as i32);
+            self.gl_context.scissor(x as i32, gl_y as i32, width as i32, height as i32);
+            self.render_view(&frame, camera, [width, height])?;
+        }
+        self.gl_context.disable(WebGlRenderingContext::SCISSOR_TEST);
+        // The rendering proccess
+        // for a single frame ends here.
+        Ok(())
+    }
+
+    /// This function will render the scene's
+    /// layers which the camera renders, into\
+    /// the current viewport (whose size is
+    /// given, in pixels).
+    ///
+    /// The indices of the object instances which
+    /// were already animated this frame are kept\
+    /// in `animated`.
+    fn render_view(&mut self, frame: &FrameState, camera: &element::Camera, resolution: [f32; 2])
+     -> Result<(), JsValue> {
+        let FrameState { game, scene_props, object_stack, asset_defs, elapsed } = *frame;
+        // Every layer is drawn with a view which is
+        // based on the one the scripts convert points\
+        // with (see `LayerView::new`). The view of the
+        // quads in the vertex vector is kept here.
+        let cam_view = camera.view(game.angle_unit);
+        let cam_color = [from_0_225_to_0_1(camera.color.r), from_0_225_to_0_1(camera.color.g),
+        from_0_225_to_0_1(camera.color.b), from_0_225_to_0_1(camera.color.a)];
+        let mut cur_view: Option<LayerView> = None;
 
         // This set of variables will store
         // data for different sprites in
@@ -466,11 +580,49 @@ impl WebGlRenderer {
         let mut texcoord_1: [f32; 2] = [0.0, 0.0];
         let mut texcoord_2: [f32; 2] = [0.0, 0.0];
         let mut origin_minus_offset: [f32; 2] = [0.0, 0.0];
+        // Put the object instances of the layers
+        // the camera renders in the order they\
+        // should be drawn in (see `element::SortMode`).
+        self.draw_order.clear();
+        self.layer_views.clear();
+        for layer in scene_props.layers[0..scene_props.layers_len].iter()
+        .filter(|layer| layer.visible && camera.renders_layer(&layer.name)) {
+            let start = self.draw_order.len();
+            let view = self.layer_views.len() as u32;
+            self.layer_views.push(LayerView::new(layer, cam_view, cam_color, resolution));
+            self.draw_order.extend(layer.instances.iter().enumerate().map(|(order, &index)| DrawEntry {
+                view,
+                order: order as u32,
+                index,
+                key: if layer.sort_mode == element::SortMode::Insertion { 0.0 } else {
+                    object_stack.get(index as usize).map_or(0.0, |object| layer.sort_mode.key(
+                    &object.properties.borrow().read_lock::<element::Object>().expect("read lock should succeed.")))
+                },
+            }));
+            // The unstable sort doesn't allocate, and
+            // breaking the ties with the insertion\
+            // order keeps the result stable anyway.
+            if layer.sort_mode != element::SortMode::Insertion {
+                self.draw_order[start..].sort_unstable_by(|a, b| a.key.total_cmp(&b.key).then(a.order.cmp(&b.order)));
+            }
+        }
         // Iterate over the scene's object
         // instances in the order of the
         // layers they are in and render them.
-        for &index in scene_props.layers[0..scene_props.layers_len].iter()
-        .flat_map(|layer| { layer.instances.iter() }) {
+        for entry_idx in 0..self.draw_order.len() {
+            let DrawEntry { view, index, .. } = self.draw_order[entry_idx];
+            let view = self.layer_views[view as usize];
+            // When the view changes, draw what's in
+            // the vertex vector with the view it was\
+            // generated for, before switching to it.
+            if cur_view != Some(view) {
+                self.flush();
+                self.set_view(&view, resolution)?;
+                cur_view = Some(view);
+            }
+            // An object instance which more than one
+            // camera renders is only animated once.
+            let elapsed = if self.animated.insert(index) { elapsed } else { 0.0 };
             // If the vertex vector is full,
             if (self.vertex_vec.len() as i32) / (FLOATS_PER_VERTEX * VERTICES_PER_QUAD * MAX_QUAD_COUNT) >= 1 {
                 // flush all the data from the vertex vector
@@ -487,6 +639,10 @@ impl WebGlRenderer {
                 let mut object_or_sprite = object_or_sprite
                 .write_lock::<element::Object>()
                 .expect("write lock should succeed.");
+                // Skip the object if it's hidden.
+                if !object_or_sprite.visible {
+                    continue;
+                }
 
                 // Here the object will switch to a sprite.
                 // This needs to be done because the sprite's
@@ -730,24 +886,58 @@ impl WebGlRenderer {
 
                 // Generate the quad which will be used
                 // to render the sprite, and add it to
-                // the vertex vector.
-                self.vertex_vec.extend_from_slice(&generate_textured_quad(object_or_sprite.position.x.floor() - 
-                (origin_minus_offset[0] * object_or_sprite.scale.x), object_or_sprite.position.y.floor() - 
+                // the vertex vector. The quad is placed
+                // so the sprite's origin (minus the frame's
+                // offset) is on the object's position, and
+                // it's rotated around that position (a
+                // negative scale flips it before that).
+                let pivot = [object_or_sprite.position.x.floor(), object_or_sprite.position.y.floor()];
+                self.vertex_vec.extend_from_slice(&generate_textured_quad(pivot[0] -
+                (origin_minus_offset[0] * object_or_sprite.scale.x), pivot[1] -
                 (origin_minus_offset[1] * object_or_sprite.scale.y), [from_0_225_to_0_1(object_or_sprite.color.r),
                 from_0_225_to_0_1(object_or_sprite.color.g),from_0_225_to_0_1(object_or_sprite.color.b),
                 from_0_225_to_0_1(object_or_sprite.color.a)], quad_width * object_or_sprite.scale.x,
                 quad_height * object_or_sprite.scale.y, texcoord_1, texcoord_2,
                 [tex_width, tex_height], [object_or_sprite.scale.x, object_or_sprite.scale.y],
-                unit_id));
+                unit_id, pivot, game.angle_unit.to_radians(object_or_sprite.rotation)));
             }
         }
         // Flush all the data that's left in the
         // vertex vector into the vertex buffer,
-        // draw the scene, and clear the texture
+        // draw the view, and clear the texture
         // slots vector.
         self.flush();
-        // The rendering proccess
-        // for a single frame ends here.
+        Ok(())
+    }
+
+    /// Sets the uniform values and the blending
+    /// method of the view the next quads will\
+    /// be drawn with.
+    fn set_view(&self, view: &LayerView, resolution: [f32; 2]) -> Result<(), JsValue> {
+        if let Some(location) = self.uniform_locations.get("u_camera") {
+            self.gl_context.uniform2f(Some(location), view.position[0], view.position[1]);
+        } else { return Err("Couldn't find uniform 'u_camera'".into()); }
+        if let Some(location) = self.uniform_locations.get("u_zoom") {
+            self.gl_context.uniform1f(Some(location), view.zoom);
+        } else { return Err("Couldn't find uniform 'u_zoom'".into()); }
+        if let Some(location) = self.uniform_locations.get("u_cam_rotation") {
+            self.gl_context.uniform1f(Some(location), view.rotation);
+        } else { return Err("Couldn't find uniform 'u_cam_rotation'".into()); }
+        if let Some(location) = self.uniform_locations.get("u_cam_color") {
+            self.gl_context.uniform4f(Some(location), view.color[0], view.color[1], view.color[2], view.color[3]);
+        } else { return Err("Couldn't find uniform 'u_cam_color'".into()); }
+        if let Some(location) = self.uniform_locations.get("u_resolution") {
+            self.gl_context.uniform2f(Some(location), resolution[0], resolution[1]);
+        } else { return Err("Couldn't find uniform 'u_resolution'".into()); }
+        // The source's alpha is used like in the
+        // normal blend mode whenever it can be.
+        let (source, destination) = match view.blend_mode {
+            element::BlendMode::Normal => (WebGlRenderingContext::SRC_ALPHA, WebGlRenderingContext::ONE_MINUS_SRC_ALPHA),
+            element::BlendMode::Additive => (WebGlRenderingContext::SRC_ALPHA, WebGlRenderingContext::ONE),
+            element::BlendMode::Multiply => (WebGlRenderingContext::DST_COLOR, WebGlRenderingContext::ONE_MINUS_SRC_ALPHA),
+            element::BlendMode::Screen => (WebGlRenderingContext::ONE, WebGlRenderingContext::ONE_MINUS_SRC_COLOR),
+        };
+        self.gl_context.blend_func(source, destination);
         Ok(())
     }
 
@@ -801,7 +991,7 @@ impl WebGlRenderer {
 fn activate_context(width: f32, height: f32) -> Result<WebGlRenderingContext, JsValue> {
     // Don't allow the canvas webgl
     // context to be used more than once.
-    if unsafe { CANVAS_ID.is_empty() } {
+    if unsafe { CANVAS_ID }.is_empty() {
         return Err("The canvas webgl context was already used.".into());
     }
     // Get the page's document.
@@ -821,13 +1011,11 @@ fn activate_context(width: f32, height: f32) -> Result<WebGlRenderingContext, Js
 
     // Get the WebGL context
     // from the canvas.
+    let attributes = WebGlContextAttributes::new();
+    attributes.set_alpha(false);
+    attributes.set_premultiplied_alpha(true);
     let context = canvas
-    .get_context_with_context_options("webgl",
-        WebGlContextAttributes::new()
-        .alpha(false)
-        .premultiplied_alpha(true)
-        .dyn_ref::<JsValue>().unwrap()
-    )?
+    .get_context_with_context_options("webgl", attributes.dyn_ref::<JsValue>().unwrap())?
     .unwrap()
     .dyn_into::<WebGlRenderingContext>()?;
     // Enable premultiplied alpha unpacking,
@@ -849,25 +1037,25 @@ fn create_program(gl_context: &WebGlRenderingContext)
  -> Result<(WebGlProgram,HashMap<String, web_sys::WebGlUniformLocation>), JsValue> {
     // Create the vertex shader
     let vert_shader = compile_shader(
-        &gl_context,
+        gl_context,
         WebGlRenderingContext::VERTEX_SHADER,
         VERTEX_SHADER,
     )?;
 
     // Create the fragment shader
     let frag_shader = compile_shader(
-        &gl_context,
+        gl_context,
         WebGlRenderingContext::FRAGMENT_SHADER,
         FRAGMENT_SHADER,
     )?;
 
     // Create the shader program using
     // the vertex and fragment shaders.
-    let gl_program = link_program(&gl_context, &vert_shader, &frag_shader,
+    let gl_program = link_program(gl_context, &vert_shader, &frag_shader,
     Some(|gl_context: &WebGlRenderingContext, gl_program: &WebGlProgram| {
         // Bind attribute locations.
         for (index, &(attribute,_,_)) in ATTRIBUTE_MATRIX.iter().enumerate() {
-            gl_context.bind_attrib_location(&gl_program, index as u32, attribute);
+            gl_context.bind_attrib_location(gl_program, index as u32, attribute);
         }
     }))?;
 
@@ -887,26 +1075,31 @@ fn create_program(gl_context: &WebGlRenderingContext)
 
 /// Creates an array of f32 floats with
 /// the vertices which should represent\
-/// a desired textured rectangle.
+/// a desired textured rectangle, which
+/// the vertex shader rotates around\
+/// the pivot.
+#[allow(clippy::too_many_arguments)]
 fn generate_textured_quad(x: f32, y: f32, color: [f32; 4],
 width: f32, height: f32, texpoint_1: [f32; 2],
 texpoint_2: [f32; 2], tex_size: [f32; 2],
-scale: [f32; 2], texunit_id: f32) -> [f32; (VERTICES_PER_QUAD * FLOATS_PER_VERTEX) as usize] {
+scale: [f32; 2], texunit_id: f32, pivot: [f32; 2], rotation: f32)
+-> [f32; (VERTICES_PER_QUAD * FLOATS_PER_VERTEX) as usize] {
     let x1 = x;
     let x2 = x + width;
     let y1 = y;
     let y2 = y + height;
     //  x, y, red, green, blue, alpha, texture_x(0-1), texture_y(0-1),
-    //  tex_width, tex_height, scale_x, scale_y, texture_unit_id
+    //  tex_width, tex_height, scale_x, scale_y, texture_unit_id,
+    //  pivot_x, pivot_y, rotation(radians)
     [
         x1, y1, color[0], color[1], color[2], color[3], texpoint_1[0], texpoint_1[1],
-        tex_size[0], tex_size[1], scale[0], scale[1], texunit_id,
+        tex_size[0], tex_size[1], scale[0], scale[1], texunit_id, pivot[0], pivot[1], rotation,
         x2, y1, color[0], color[1], color[2], color[3], texpoint_2[0], texpoint_1[1],
-        tex_size[0], tex_size[1], scale[0], scale[1], texunit_id,
+        tex_size[0], tex_size[1], scale[0], scale[1], texunit_id, pivot[0], pivot[1], rotation,
         x1, y2, color[0], color[1], color[2], color[3], texpoint_1[0], texpoint_2[1],
-        tex_size[0], tex_size[1], scale[0], scale[1], texunit_id,
+        tex_size[0], tex_size[1], scale[0], scale[1], texunit_id, pivot[0], pivot[1], rotation,
         x2, y2, color[0], color[1], color[2], color[3], texpoint_2[0], texpoint_2[1],
-        tex_size[0], tex_size[1], scale[0], scale[1], texunit_id,
+        tex_size[0], tex_size[1], scale[0], scale[1], texunit_id, pivot[0], pivot[1], rotation,
     ]
 }
 
@@ -914,7 +1107,7 @@ scale: [f32; 2], texunit_id: f32) -> [f32; (VERTICES_PER_QUAD * FLOATS_PER_VERTE
 /// go from 0 to 1 instead of from\
 /// 0 to 255, for use with the WebGL context.
 fn from_0_225_to_0_1(color: u8) -> f32 {
-    return (color as f32) / 255_f32; 
+    (color as f32) / 255_f32
 }
 
 /// Compiles a shader and
@@ -966,7 +1159,7 @@ fn link_program(
     gl_context: &WebGlRenderingContext,
     vert_shader: &WebGlShader,
     frag_shader: &WebGlShader,
-    before_link: Option<impl Fn(&WebGlRenderingContext, &WebGlProgram) -> ()>,
+    before_link: Option<impl Fn(&WebGlRenderingContext, &WebGlProgram)>,
 ) -> Result<WebGlProgram, String> {
     // Create a program with the
     // provided WebGL context.
@@ -980,7 +1173,7 @@ fn link_program(
     // Call the provided function
     // before linking the program.
     if let Some(before_link) = before_link {
-        before_link(&gl_context, &program);
+        before_link(gl_context, &program);
     }
     // Link the program to the
     // provided WebGL context.
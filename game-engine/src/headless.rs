@@ -0,0 +1,117 @@
+use std::io::{BufRead, Write};
+
+use crate::{data, game::{HeadlessGame, engine_api::{repl, profiler, testing}}};
+
+/// How to use the headless runner,
+/// printed when it's used wrong.
+const USAGE: &str = "Usage: headless (test | repl) <project file>";
+
+/// The console's commands, printed by ':help'.
+/// Any other line is evaluated as code.
+const REPL_HELP: &str = concat!(
+    ":target state-manager | scene | object <index> - evaluates the code on another element\n",
+    ":step [frames] - runs frames of the game (1 by default)\n",
+    ":profile [on | off] - prints the profiler's timings, or turns the profiler on or off\n",
+    ":quit - exits the console\n",
+);
+
+/// Runs the headless runner with the given
+/// command line arguments (without the\
+/// program's name), and returns the exit code.
+///
+/// The `test` command runs every test of the
+/// game in the project file (or game data\
+/// file), prints their outcomes, and fails
+/// if any of them failed, so a CI job can\
+/// run the tests without a browser.
+///
+/// The `repl` command starts the game, and
+/// evaluates the lines it reads from stdin\
+/// in the context of it's elements, like the
+/// host page's console does.
+pub fn run(args: &[String]) -> i32 {
+    let [command, project] = args else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    if command != "test" && command != "repl" {
+        eprintln!("{}", USAGE);
+        return 2;
+    }
+    if let Err(err) = data::open_project(project) {
+        eprintln!("{}", err);
+        return 2;
+    }
+    if command == "test" {
+        let results = testing::run_tests();
+        print!("{}", testing::text_report(&results));
+        return if results.iter().all(|result| result.error.is_none()) { 0 } else { 1 };
+    }
+    match HeadlessGame::new() {
+        Ok(game) => { run_repl(&game, std::io::stdin().lock(), std::io::stdout()); 0 },
+        Err(err) => { eprintln!("{}", err); 1 },
+    }
+}
+
+/// Reads the console's lines from `input`
+/// until it ends, and writes the results\
+/// (or errors) of the lines to `output`.
+fn run_repl(game: &HeadlessGame, input: impl BufRead, mut output: impl Write) {
+    let mut target = (String::from("state-manager"), 0_u32);
+    let mut print = |text: &str| { writeln!(output, "{}", text).expect("The console's output should be writable."); };
+    for line in input.lines() {
+        let Ok(line) = line else { break; };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => {},
+            [":quit"] => break,
+            [":help"] => print(REPL_HELP),
+            [":profile"] => print(&profiler::text_report()),
+            [":profile", "on"] => profiler::profile_enable(true),
+            [":profile", "off"] => profiler::profile_enable(false),
+            [":target", name @ ("state-manager" | "scene")] => target = (String::from(*name), 0),
+            [":target", "object", index] => match index.parse() {
+                Ok(index) => target = (String::from("object"), index),
+                Err(_) => print(&format!("'{}' isn't an object index.", index)),
+            },
+            [":step", frames @ ..] if frames.len() <= 1 => {
+                let Ok(frames) = frames.first().map_or(Ok(1), |frames| frames.parse::<u32>()) else {
+                    print(&format!("'{}' isn't a number of frames.", frames[0]));
+                    continue;
+                };
+                if let Some(Err(err)) = (0..frames).map(|_| game.step()).find(Result::is_err) { print(&err); }
+            },
+            [command, ..] if command.starts_with(':') => print(&format!("Unknown command '{}' (see ':help').", line)),
+            _ => print(&repl::eval(&target.0, target.1, &line).unwrap_or_else(|err| err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_lines_and_commands() {
+        let game = HeadlessGame::for_test("State[\"frames\"] = 0;\nfn update(elapsed) { State[\"frames\"] += 1; }",
+        &[("scene", 2, crate::game::TEST_SCENE_CONFIG, "let here = \"scene\";")]);
+        let mut output = Vec::new();
+        run_repl(&game, concat!(
+            "1 + 2\n",
+            ":step 3\n",
+            "State[\"frames\"]\n",
+            ":target scene\n",
+            "here\n",
+            ":target object 5\n",
+            "1\n",
+            ":bogus\n",
+            ":quit\n",
+            "unreached\n").as_bytes(), &mut output);
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[..3], ["3", "3", "\"scene\""]);
+        assert!(lines[3].contains("doesn't exist"), "{}", output);
+        assert!(lines[4].starts_with("Unknown command"), "{}", output);
+        assert_eq!(lines.len(), 5);
+    }
+}
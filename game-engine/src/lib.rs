@@ -2,7 +2,6 @@
 use std::panic;
 
 use wasm_bindgen::prelude::*;
-use console_error_panic_hook;
 
 /// Defines the methods which
 /// let you load data from the
@@ -10,6 +9,10 @@ use console_error_panic_hook;
 mod data;
 /// Defines the game engine.
 mod game;
+/// Defines the headless runner, which runs the
+/// game's tests and console without a browser.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod headless;
 
 #[wasm_bindgen(start)]
 pub fn main() -> Result<(), JsValue> {